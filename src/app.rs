@@ -1,17 +1,28 @@
+use color_eyre::eyre::WrapErr;
 use color_eyre::Result;
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::Size;
 use ratatui::text::Text;
 use ratatui::widgets::Paragraph;
 use ratatui::{style::Stylize, text::Line, widgets::Block, DefaultTerminal, Frame};
 use std::fmt;
-use std::time::Duration;
-use sweeper_controller::SweeperController;
-use sweeper_view::draw_game;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use sweeper_controller::{parse_coordinate, SweeperController};
 
+use crate::model::sweeper::{FlagResult, GameState, SweeperGame};
+use replays::ReplayMeta;
+use settings::Settings;
+use sweeper_view::{draw_game, required_size, ANIMATION_FRAMES, TICKS_PER_FRAME};
+
+pub mod replays;
+mod settings;
 pub mod sweeper_controller;
 pub mod sweeper_view;
+pub mod theme;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum Difficulty {
     Beginner,
     Intermediate,
@@ -39,6 +50,21 @@ impl Difficulty {
         }
     }
 
+    const ALL: [Difficulty; 3] = [
+        Difficulty::Beginner,
+        Difficulty::Intermediate,
+        Difficulty::Expert,
+    ];
+
+    /// Preset whose setting matches the game's dimensions and bomb count.
+    fn of_game(game: &SweeperGame) -> Option<Self> {
+        Self::ALL.into_iter().find(|difficulty| {
+            let setting = difficulty.setting();
+            (setting.width, setting.height, setting.bomb_count)
+                == (game.get_width(), game.get_height(), game.num_bombs)
+        })
+    }
+
     fn next(&self) -> Self {
         match self {
             Difficulty::Beginner => Difficulty::Intermediate,
@@ -46,6 +72,14 @@ impl Difficulty {
             Difficulty::Expert => Difficulty::Beginner,
         }
     }
+
+    fn prev(&self) -> Self {
+        match self {
+            Difficulty::Beginner => Difficulty::Expert,
+            Difficulty::Intermediate => Difficulty::Beginner,
+            Difficulty::Expert => Difficulty::Intermediate,
+        }
+    }
 }
 
 impl fmt::Display for Difficulty {
@@ -72,66 +106,379 @@ struct GameSetting {
     bomb_count: usize,
 }
 
+/// Summary of the 3BV over a set of sample boards.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Bv3Stats {
+    mean: f64,
+    min: usize,
+    max: usize,
+}
+
+impl GameSetting {
+    /// Terminal size needed to draw a game of this setting.
+    fn required_size(&self, settings: &Settings) -> Size {
+        let mut game = SweeperGame::new(self.width, self.height, self.bomb_count);
+        game.flag_budget = settings.flag_budget;
+        let (width, height) = required_size(game, settings.view, &settings.theme);
+        Size::new(
+            width.try_into().unwrap_or(u16::MAX),
+            height.try_into().unwrap_or(u16::MAX),
+        )
+    }
+
+    /// Generate throwaway boards and summarize their 3BV.
+    fn sample_3bv(&self, samples: usize) -> Bv3Stats {
+        let bvs: Vec<usize> = (0..samples.max(1))
+            .map(|_| {
+                let mut game = SweeperGame::new(self.width, self.height, self.bomb_count);
+                game.generate_board(self.width as isize / 2, self.height as isize / 2)
+                    .expect("center of the board is a valid first click");
+                game.three_bv()
+            })
+            .collect();
+
+        Bv3Stats {
+            mean: bvs.iter().sum::<usize>() as f64 / bvs.len() as f64,
+            min: *bvs.iter().min().unwrap(),
+            max: *bvs.iter().max().unwrap(),
+        }
+    }
+}
+
+/// Number of boards sampled for the menu's 3BV preview.
+const BV3_SAMPLES: usize = 20;
+
+/// Ring the terminal bell as feedback for an ignored action.
+fn beep() {
+    let mut stdout = io::stdout();
+    let _ = stdout.write_all(b"\x07").and_then(|_| stdout.flush());
+}
+
+/// Directory named by the environment variable `name`, if it is set and not empty.
+fn env_dir(name: &str) -> Option<PathBuf> {
+    std::env::var_os(name)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Number of fastest replays listed in the menu.
+const MENU_REPLAYS: usize = 5;
+
+/// Default minimum interval between two presses of the same key.
+const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// Whether a key press repeats the last accepted press too quickly and should be ignored.
+fn is_debounced(
+    last: Option<(KeyCode, Instant)>,
+    code: KeyCode,
+    now: Instant,
+    interval: Duration,
+) -> bool {
+    match last {
+        Some((last_code, last_time)) => {
+            last_code == code && now.saturating_duration_since(last_time) < interval
+        }
+        None => false,
+    }
+}
+
+/// Progress of the game over animation, advanced once per loop iteration.
+#[derive(Debug, Default)]
+struct AnimationState {
+    /// State of the shown game when last ticked.
+    state: Option<GameState>,
+    /// Ticks since `state` last changed.
+    ticks: usize,
+}
+
+impl AnimationState {
+    /// Advance by one tick, restarting when the shown game's state changes.
+    /// Returns whether a new frame is due.
+    fn tick(&mut self, state: Option<GameState>) -> bool {
+        if state != self.state {
+            self.state = state;
+            self.ticks = 0;
+            return true;
+        }
+        self.ticks = self.ticks.saturating_add(1);
+        state.is_some_and(|state| state.is_terminal())
+            && self.ticks.is_multiple_of(TICKS_PER_FRAME)
+            && self.ticks / TICKS_PER_FRAME < ANIMATION_FRAMES
+    }
+}
+
 #[derive(Debug)]
 pub struct App {
     controller: SweeperController,
     state: AppState,
-    difficulty: Difficulty,
+    settings: Settings,
+    bv3_stats: Bv3Stats,
+    /// Coordinate typed so far while in go-to mode.
+    goto_input: Option<String>,
+    last_key: Option<(KeyCode, Instant)>,
+    /// Where won games are saved, if there is a data directory.
+    replay_dir: Option<PathBuf>,
+    /// Saved replays, fastest first.
+    replays: Vec<ReplayMeta>,
+    selected_replay: usize,
+    /// Spoken-style summary of the game, shown until the next key press.
+    summary: Option<String>,
+    animation: AnimationState,
+    terminal_size: Size,
+    /// Shown in the menu when a game did not fit the terminal.
+    size_warning: Option<String>,
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl App {
-    /// Construct a new instance of [`App`].
+    /// Construct a new instance of [`App`] that assumes any board fits the terminal.
     pub fn new() -> Self {
+        Self::new_with_terminal_size(Size::new(u16::MAX, u16::MAX))
+    }
+
+    /// Construct a new instance of [`App`] for a terminal of the given size, which is tracked
+    /// through resize events afterwards.
+    pub fn new_with_terminal_size(terminal_size: Size) -> Self {
+        Self::with_settings(Settings::load(), replays::replay_dir(), terminal_size)
+    }
+
+    /// App using the given settings and replay directory instead of the user's.
+    fn with_settings(settings: Settings, replay_dir: Option<PathBuf>, terminal_size: Size) -> Self {
+        let mut controller = SweeperController::new();
+        settings.configure(&mut controller);
         Self {
-            controller: SweeperController::new(),
+            controller,
             state: AppState::Menu,
-            difficulty: Difficulty::Beginner,
+            bv3_stats: settings.difficulty.setting().sample_3bv(BV3_SAMPLES),
+            settings,
+            goto_input: None,
+            last_key: None,
+            replays: replay_dir
+                .as_deref()
+                .map_or_else(Vec::new, replays::list_replays_in),
+            replay_dir,
+            selected_replay: 0,
+            summary: None,
+            animation: AnimationState::default(),
+            terminal_size,
+            size_warning: None,
         }
     }
 
     /// Main application loop
     pub fn run(&mut self, mut terminal: DefaultTerminal) -> Result<()> {
+        let mut needs_redraw = true;
         while self.state != AppState::Exit {
-            terminal.draw(|frame| self.draw(frame))?;
-            self.handle_crossterm_events()?;
+            if needs_redraw {
+                terminal.draw(|frame| self.draw(frame))?;
+            }
+            let handled_event = self.handle_crossterm_events()?;
+            let ticked = self.controller.tick();
+            self.save_won_replays();
+            let animated = self
+                .animation
+                .tick(self.controller.game().map(|game| game.state));
+            // Skip redraws when nothing visible changed
+            needs_redraw = handled_event
+                | ticked
+                | animated
+                | self.controller.seconds_changed_since_last_draw();
         }
+        self.settings.save().wrap_err("failed to save settings")?;
         Ok(())
     }
 
+    /// Return to the menu, refreshing the list of replays.
+    fn show_menu(&mut self) {
+        self.state = AppState::Menu;
+        self.replays = self
+            .replay_dir
+            .as_deref()
+            .map_or_else(Vec::new, replays::list_replays_in);
+        self.selected_replay = self
+            .selected_replay
+            .min(self.replays.len().min(MENU_REPLAYS).saturating_sub(1));
+    }
+
+    /// Save the recordings of freshly won games of a preset difficulty.
+    fn save_won_replays(&mut self) {
+        let won = self.controller.take_won_replays();
+        let Some(dir) = self.replay_dir.as_deref().filter(|_| !won.is_empty()) else {
+            return;
+        };
+        for (replay, time) in won {
+            let difficulty = SweeperGame::from_bytes(&replay.board)
+                .ok()
+                .and_then(|game| Difficulty::of_game(&game));
+            if let Some(difficulty) = difficulty {
+                let name = difficulty.to_string();
+                let _ = replays::save_replay_in(dir, &replay, &name, time, replays::today());
+            }
+        }
+    }
+
+    fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.settings.difficulty = difficulty;
+        self.bv3_stats = difficulty.setting().sample_3bv(BV3_SAMPLES);
+    }
+
+    /// Play back the replay selected in the menu.
+    fn play_selected_replay(&mut self) {
+        let Some(meta) = self.replays.get(self.selected_replay) else {
+            return;
+        };
+        let played = replays::load_replay(&meta.path)
+            .ok()
+            .is_some_and(|replay| self.controller.play_replay(replay).is_ok());
+        if played {
+            self.state = AppState::InGame;
+            self.goto_input = None;
+        } else {
+            beep();
+        }
+    }
+
+    /// Whether a game of the selected difficulty fits the terminal. If not, sets a warning.
+    fn check_terminal_fits(&mut self) -> bool {
+        let required = self
+            .settings
+            .difficulty
+            .setting()
+            .required_size(&self.settings);
+        if required.width <= self.terminal_size.width
+            && required.height <= self.terminal_size.height
+        {
+            return true;
+        }
+        self.size_warning = Some(format!(
+            "Terminal too small for {}: need {}x{}",
+            self.settings.difficulty, required.width, required.height
+        ));
+        false
+    }
+
+    /// Start a game of the selected difficulty, or show the menu with a warning if it does not
+    /// fit the terminal.
     fn start_game(&mut self) {
+        if !self.check_terminal_fits() {
+            self.show_menu();
+            return;
+        }
         self.state = AppState::InGame;
+        self.goto_input = None;
         self.controller.start_game(
-            self.difficulty.setting().width,
-            self.difficulty.setting().height,
-            self.difficulty.setting().bomb_count,
+            self.settings.difficulty.setting().width,
+            self.settings.difficulty.setting().height,
+            self.settings.difficulty.setting().bomb_count,
         );
     }
 
+    /// Open another board next to the current ones. If it does not fit the terminal, the
+    /// current game stays on screen with a warning.
+    fn add_game(&mut self) {
+        if !self.check_terminal_fits() {
+            return;
+        }
+        self.controller.add_game(
+            self.settings.difficulty.setting().width,
+            self.settings.difficulty.setting().height,
+            self.settings.difficulty.setting().bomb_count,
+        );
+    }
+
+    /// Close the current board, returning to the menu after the last one.
+    fn close_game(&mut self) {
+        self.controller.close_game();
+        if self.controller.game().is_none() {
+            self.show_menu();
+        }
+    }
+
     /// Renders the user interface.
     ///
     /// This is where you add new widgets. See the following resources for more information:
     /// - <https://docs.rs/ratatui/latest/ratatui/widgets/index.html>
     /// - <https://github.com/ratatui/ratatui/tree/master/examples>
     fn draw(&mut self, frame: &mut Frame) {
-        let title = Line::from("Rust Sweeper ".blue().bold()).centered();
+        let mut title = Line::from("Rust Sweeper ".blue().bold()).centered();
+        if self.state == AppState::InGame && self.controller.game_count() > 1 {
+            title.push_span(format!(
+                "[{}/{}] ",
+                self.controller.active_index() + 1,
+                self.controller.game_count()
+            ));
+        }
+        let mut block = Block::bordered().title(title);
+        if let Some(ref input) = self.goto_input {
+            let goto_line = Line::from(format!(" Go to: {input}_ ").bold()).centered();
+            block = block.title_bottom(goto_line);
+        } else if let Some(ref summary) = self.summary {
+            block = block.title_bottom(Line::from(format!(" {summary} ")).centered());
+        }
 
         frame.render_widget(
             match self.state {
                 AppState::Menu => {
-                    let difficulty_text = format!("Difficulty: {} ('d')", self.difficulty);
+                    let difficulty_text =
+                        format!("Difficulty: {} ('d'/'D')", self.settings.difficulty);
                     let difficulty_line = Line::from(difficulty_text.bold());
+                    let bv3_text = format!(
+                        "3BV: {:.1} avg ({}-{})",
+                        self.bv3_stats.mean, self.bv3_stats.min, self.bv3_stats.max
+                    );
+                    let bv3_line = Line::from(bv3_text);
                     let start_line = Line::from("Press 'n' to start a new game".bold());
                     let quit_line = Line::from("Press 'q' to quit".bold());
-                    let lines = vec![difficulty_line, start_line, quit_line];
+                    let mut lines = vec![difficulty_line, bv3_line, start_line, quit_line];
+                    if let Some(ref warning) = self.size_warning {
+                        lines.push(Line::from(warning.clone().red().bold()));
+                    }
+                    if !self.replays.is_empty() {
+                        lines.push(Line::default());
+                        lines.push(Line::from("Best replays (Enter to watch)".bold()));
+                    }
+                    for (i, meta) in self.replays.iter().take(MENU_REPLAYS).enumerate() {
+                        let (year, month, day) = meta.date;
+                        let text = format!(
+                            "{} {:.3}s {year:04}-{month:02}-{day:02}",
+                            meta.difficulty,
+                            meta.time.as_secs_f64()
+                        );
+                        lines.push(if i == self.selected_replay {
+                            Line::from(format!("> {text} <").bold())
+                        } else {
+                            Line::from(text)
+                        });
+                    }
                     Paragraph::new(Text::from(lines))
                 }
-                AppState::InGame => draw_game(
-                    self.controller.game.as_ref().unwrap(),
-                    self.controller.cursor,
-                ),
+                AppState::InGame => match self.controller.game() {
+                    Some(game) => {
+                        let mut text = draw_game(
+                            game,
+                            self.controller.cursor(),
+                            self.settings.view,
+                            &self.settings.theme,
+                            self.animation.ticks,
+                        )
+                        .into_text();
+                        if let Some(ref warning) = self.size_warning {
+                            text.lines.push(Line::from(warning.clone().red().bold()));
+                        }
+                        Paragraph::new(text)
+                    }
+                    None => Paragraph::new(Text::from(Line::from(
+                        "No game in progress. Press 'n' to start one".bold(),
+                    ))),
+                },
                 _ => Paragraph::new(Text::from(Line::from("Goodbye!"))),
             }
-            .block(Block::bordered().title(title))
+            .block(block)
             .centered(),
             frame.area(),
         );
@@ -141,23 +488,34 @@ impl App {
     ///
     /// If your application needs to perform work in between handling events, you can use the
     /// [`event::poll`] function to check if there are any events available with a timeout.
-    fn handle_crossterm_events(&mut self) -> Result<()> {
+    /// Returns whether an event was read.
+    fn handle_crossterm_events(&mut self) -> Result<bool> {
         if event::poll(Duration::from_millis(100))? {
             match event::read()? {
                 // it's important to check KeyEventKind::Press to avoid handling key release events
-                Event::Key(key) if key.kind == KeyEventKind::Press => self.on_key_event(key),
+                Event::Key(key) if key.kind == KeyEventKind::Press => {
+                    let now = Instant::now();
+                    if !is_debounced(self.last_key, key.code, now, self.settings.debounce) {
+                        self.last_key = Some((key.code, now));
+                        self.on_key_event(key);
+                    }
+                }
                 Event::Mouse(_) => {}
-                Event::Resize(_, _) => {}
+                Event::Resize(width, height) => self.terminal_size = Size::new(width, height),
                 _ => {}
             }
+            return Ok(true);
         }
-        Ok(())
+        Ok(false)
     }
 
     /// Handles the key events and updates the state of [`App`].
     fn on_key_event(&mut self, key: KeyEvent) {
+        self.summary = None;
+        self.size_warning = None;
         match (key.modifiers, key.code) {
             (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) => self.quit(),
+            _ if self.goto_input.is_some() => self.on_goto_key_event(key),
             (_, KeyCode::Char('n')) => self.start_game(),
             _ => match self.state {
                 AppState::Menu => self.on_menu_key_event(key),
@@ -172,34 +530,113 @@ impl App {
             (_, KeyCode::Esc | KeyCode::Char('q')) => {
                 self.quit();
             }
-            (_, KeyCode::Char('d')) => self.difficulty = self.difficulty.next(),
+            (_, KeyCode::Char('d')) => self.set_difficulty(self.settings.difficulty.next()),
+            (_, KeyCode::Char('D')) => self.set_difficulty(self.settings.difficulty.prev()),
+            (_, KeyCode::Up) => self.selected_replay = self.selected_replay.saturating_sub(1),
+            (_, KeyCode::Down) => {
+                let last = self.replays.len().min(MENU_REPLAYS).saturating_sub(1);
+                self.selected_replay = (self.selected_replay + 1).min(last);
+            }
+            (_, KeyCode::Enter) => self.play_selected_replay(),
             _ => {}
         }
     }
 
     fn on_game_key_event(&mut self, key: KeyEvent) {
+        if self.controller.is_replaying() {
+            return self.on_replay_key_event(key);
+        }
         match (key.modifiers, key.code) {
             (_, KeyCode::Esc | KeyCode::Char('q')) => {
                 if self.controller.is_running() {
                     self.controller.resign();
                 } else {
-                    self.state = AppState::Menu;
+                    self.show_menu();
                 }
             }
             (_, KeyCode::Left) => self.controller.move_cursor(-1, 0),
             (_, KeyCode::Right) => self.controller.move_cursor(1, 0),
             (_, KeyCode::Up) => self.controller.move_cursor(0, -1),
             (_, KeyCode::Down) => self.controller.move_cursor(0, 1),
-            (_, KeyCode::Char(' ')) => {
-                if self.controller.is_running() {
-                    self.controller.open();
+            (_, KeyCode::Char(' ')) if self.controller.is_running() => {
+                self.controller.open();
+            }
+            (_, KeyCode::Char('f')) if self.controller.is_running() => {
+                match self.controller.flag() {
+                    FlagResult::Ignored => beep(),
+                    FlagResult::Flagged | FlagResult::Unflagged => {}
                 }
             }
-            (_, KeyCode::Char('f')) => {
-                if self.controller.is_running() {
-                    self.controller.flag();
+            (_, KeyCode::Char('a')) if self.controller.is_running() => self.controller.abandon(),
+            (_, KeyCode::Char('s')) if self.controller.is_running() => {
+                let progress = self.controller.solve();
+                if progress.is_stuck() {
+                    beep();
                 }
             }
+            (_, KeyCode::Char('i')) => {
+                let (x, y) = self.controller.cursor();
+                self.summary = self.controller.game().map(|game| {
+                    format!("{} {}", game.describe_cursor(x, y), game.describe_status())
+                });
+            }
+            (_, KeyCode::Char('h')) => self.settings.view.assist = !self.settings.view.assist,
+            (_, KeyCode::Char('H')) => {
+                self.settings.view.safe_cells = !self.settings.view.safe_cells
+            }
+            (_, KeyCode::Char('r')) => {
+                self.settings.view.show_rulers = !self.settings.view.show_rulers
+            }
+            (_, KeyCode::Char('v')) => self.settings.view.fog = !self.settings.view.fog,
+            #[cfg(feature = "debug-peek")]
+            (KeyModifiers::CONTROL, KeyCode::Char('b')) => {
+                self.settings.view.peek = !self.settings.view.peek
+            }
+            (_, KeyCode::Tab) => self.controller.switch_by(1),
+            (_, KeyCode::BackTab) => self.controller.switch_by(-1),
+            (_, KeyCode::Char('t')) => self.add_game(),
+            (_, KeyCode::Char('w')) => self.close_game(),
+            (_, KeyCode::Char('g')) if self.controller.is_running() => {
+                self.goto_input = Some(String::new());
+            }
+            _ => {}
+        }
+    }
+
+    /// A replay only accepts leaving it and switching boards.
+    fn on_replay_key_event(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.controller.close_game();
+                self.show_menu();
+            }
+            KeyCode::Tab => self.controller.switch_by(1),
+            KeyCode::BackTab => self.controller.switch_by(-1),
+            _ => {}
+        }
+    }
+
+    /// Handles typing a coordinate such as `c7` to jump the cursor.
+    fn on_goto_key_event(&mut self, key: KeyEvent) {
+        let Some(ref mut input) = self.goto_input else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.goto_input = None,
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Enter => {
+                if let Some(game) = self.controller.game() {
+                    if let Some(cursor) =
+                        parse_coordinate(input, game.get_width(), game.get_height())
+                    {
+                        self.controller.set_cursor(cursor);
+                        self.goto_input = None;
+                    }
+                }
+            }
+            KeyCode::Char(c) if c.is_ascii_alphanumeric() => input.push(c),
             _ => {}
         }
     }
@@ -209,3 +646,195 @@ impl App {
         self.state = AppState::Exit;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::sweeper::bomb_count_for_density;
+    use sweeper_view::ViewOptions;
+
+    #[test]
+    fn test_draw_without_game() {
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let mut app = App::with_settings(Settings::default(), None, Size::new(60, 10));
+        app.state = AppState::InGame;
+        assert!(app.controller.game().is_none());
+
+        let mut terminal = Terminal::new(TestBackend::new(60, 10)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let content: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(content.contains("No game in progress"));
+    }
+
+    #[test]
+    fn test_preset_densities() {
+        for (difficulty, density) in [
+            (Difficulty::Beginner, 0.10),
+            (Difficulty::Intermediate, 0.156),
+            (Difficulty::Expert, 0.206),
+        ] {
+            let setting = difficulty.setting();
+            assert_eq!(
+                bomb_count_for_density(setting.width, setting.height, density),
+                setting.bomb_count
+            );
+        }
+    }
+
+    #[test]
+    fn test_required_size() {
+        let settings = Settings::default();
+        for (difficulty, width, height) in [
+            (Difficulty::Beginner, 22, 18),
+            (Difficulty::Intermediate, 34, 24),
+            (Difficulty::Expert, 62, 24),
+        ] {
+            assert_eq!(
+                difficulty.setting().required_size(&settings),
+                Size::new(width, height)
+            );
+        }
+
+        let rulers = Settings {
+            view: ViewOptions {
+                show_rulers: true,
+                ..settings.view
+            },
+            ..Settings::default()
+        };
+        assert_eq!(
+            Difficulty::Expert.setting().required_size(&rulers),
+            Size::new(65, 26)
+        );
+
+        // The assist line is wider than a beginner board, and a flag budget adds a line
+        let extras = Settings {
+            view: ViewOptions {
+                assist: true,
+                ..settings.view
+            },
+            flag_budget: Some(10),
+            ..Settings::default()
+        };
+        let size = Difficulty::Beginner.setting().required_size(&extras);
+        assert!(size.width > 22);
+        assert_eq!(size.height, 20);
+    }
+
+    #[test]
+    fn test_start_game_too_small() {
+        let settings = Settings {
+            difficulty: Difficulty::Expert,
+            ..Settings::default()
+        };
+        let mut app = App::with_settings(settings, None, Size::new(40, 30));
+        app.start_game();
+        assert_eq!(app.state, AppState::Menu);
+        assert!(app.controller.game().is_none());
+        assert_eq!(
+            app.size_warning.as_deref(),
+            Some("Terminal too small for Expert: need 62x24")
+        );
+
+        app.settings.difficulty = Difficulty::Intermediate;
+        app.start_game();
+        assert_eq!(app.state, AppState::InGame);
+        assert!(app.controller.game().is_some());
+    }
+
+    #[test]
+    fn test_add_game_too_small() {
+        let mut app = App::with_settings(Settings::default(), None, Size::new(40, 30));
+        app.start_game();
+        app.controller.open();
+        assert!(app.controller.is_running());
+
+        // The larger board is rejected and the running game stays on screen
+        app.settings.difficulty = Difficulty::Expert;
+        app.on_key_event(KeyEvent::from(KeyCode::Char('t')));
+        assert_eq!(app.state, AppState::InGame);
+        assert_eq!(app.controller.game_count(), 1);
+        assert!(app.controller.is_running());
+        assert_eq!(
+            app.size_warning.as_deref(),
+            Some("Terminal too small for Expert: need 62x24")
+        );
+
+        app.settings.difficulty = Difficulty::Beginner;
+        app.on_key_event(KeyEvent::from(KeyCode::Char('t')));
+        assert_eq!(app.controller.game_count(), 2);
+        assert_eq!(app.size_warning, None);
+    }
+
+    #[test]
+    fn test_difficulty_cycle() {
+        for difficulty in Difficulty::ALL {
+            assert_eq!(difficulty.next().prev(), difficulty);
+            assert_eq!(difficulty.prev().next(), difficulty);
+        }
+        assert_eq!(Difficulty::Beginner.prev(), Difficulty::Expert);
+    }
+
+    #[test]
+    fn test_animation_state() {
+        let mut animation = AnimationState::default();
+        assert!(animation.tick(Some(GameState::Running)));
+        assert!(!animation.tick(Some(GameState::Running)));
+
+        // Every frame change of the animation is due, then it holds
+        assert!(animation.tick(Some(GameState::Win)));
+        let mut due = Vec::new();
+        for _ in 0..ANIMATION_FRAMES * TICKS_PER_FRAME + 3 {
+            if animation.tick(Some(GameState::Win)) {
+                due.push(sweeper_view::animation_frame(animation.ticks));
+            }
+        }
+        assert_eq!(due, (1..ANIMATION_FRAMES).collect::<Vec<_>>());
+
+        assert!(animation.tick(None));
+        assert_eq!(animation.ticks, 0);
+    }
+
+    #[test]
+    fn test_is_debounced() {
+        let interval = Duration::from_millis(50);
+        let start = Instant::now();
+        let space = KeyCode::Char(' ');
+
+        assert!(!is_debounced(None, space, start, interval));
+
+        let last = Some((space, start));
+        assert!(is_debounced(last, space, start, interval));
+        assert!(is_debounced(
+            last,
+            space,
+            start + Duration::from_millis(49),
+            interval
+        ));
+        assert!(!is_debounced(
+            last,
+            space,
+            start + Duration::from_millis(50),
+            interval
+        ));
+        assert!(!is_debounced(last, KeyCode::Char('f'), start, interval));
+        assert!(!is_debounced(last, space, start, Duration::ZERO));
+    }
+
+    #[test]
+    fn test_sample_3bv() {
+        let setting = Difficulty::Beginner.setting();
+        let stats = setting.sample_3bv(10);
+        let safe_cells = setting.width * setting.height - setting.bomb_count;
+        assert!(stats.min >= 1);
+        assert!(stats.max <= safe_cells);
+        assert!(stats.min as f64 <= stats.mean && stats.mean <= stats.max as f64);
+    }
+}
@@ -0,0 +1,378 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::env_dir;
+
+const REPLAY_EXTENSION: &str = "replay";
+const REPLAY_HEADER: &str = "rust-sweeper replay 1";
+
+/// Move made by the player during a recorded game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayAction {
+    Open,
+    Flag,
+}
+
+/// A move together with where and when it was made.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayStep {
+    /// Game time at which the move was made.
+    pub at: Duration,
+    pub cursor: (isize, isize),
+    pub action: ReplayAction,
+}
+
+/// Recorded game: the board right after generation followed by every move.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Replay {
+    /// Board as returned by [`SweeperGame::to_bytes`](crate::model::sweeper::SweeperGame::to_bytes).
+    pub board: Vec<u8>,
+    pub steps: Vec<ReplayStep>,
+}
+
+impl Replay {
+    /// Line based text form: a header, the hex encoded board, then one move per line.
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{REPLAY_HEADER}\nboard ");
+        for byte in &self.board {
+            text.push_str(&format!("{byte:02x}"));
+        }
+        text.push('\n');
+        for step in &self.steps {
+            let action = match step.action {
+                ReplayAction::Open => "open",
+                ReplayAction::Flag => "flag",
+            };
+            let (x, y) = step.cursor;
+            text.push_str(&format!("{} {action} {x} {y}\n", step.at.as_millis()));
+        }
+        text
+    }
+
+    /// Parse the output of [`Self::to_text`], returning `None` if it is malformed.
+    pub fn from_text(text: &str) -> Option<Self> {
+        let mut lines = text.lines();
+        if lines.next()? != REPLAY_HEADER {
+            return None;
+        }
+        let hex = lines.next()?.strip_prefix("board ")?;
+        if hex.len() % 2 != 0 {
+            return None;
+        }
+        let board = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+            .collect::<Option<Vec<u8>>>()?;
+
+        let steps = lines
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let mut fields = line.split_whitespace();
+                let at = Duration::from_millis(fields.next()?.parse().ok()?);
+                let action = match fields.next()? {
+                    "open" => ReplayAction::Open,
+                    "flag" => ReplayAction::Flag,
+                    _ => return None,
+                };
+                let x = fields.next()?.parse().ok()?;
+                let y = fields.next()?.parse().ok()?;
+                fields.next().is_none().then_some(ReplayStep {
+                    at,
+                    cursor: (x, y),
+                    action,
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Self { board, steps })
+    }
+}
+
+/// Summary of a saved replay, read from its file name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayMeta {
+    pub difficulty: String,
+    /// Time taken to win.
+    pub time: Duration,
+    /// Date the game was played as `(year, month, day)`.
+    pub date: (u16, u8, u8),
+    pub path: PathBuf,
+}
+
+/// File name of a replay, such as `Beginner-12345-20240131.replay` for a win in 12.345s.
+/// Copies after the first get a number, as in `Beginner-12345-20240131-1.replay`.
+pub fn replay_file_name(
+    difficulty: &str,
+    time: Duration,
+    (year, month, day): (u16, u8, u8),
+    copy: usize,
+) -> String {
+    let suffix = if copy == 0 {
+        String::new()
+    } else {
+        format!("-{copy}")
+    };
+    format!(
+        "{difficulty}-{}-{year:04}{month:02}{day:02}{suffix}.{REPLAY_EXTENSION}",
+        time.as_millis()
+    )
+}
+
+/// Parse a name produced by [`replay_file_name`].
+fn parse_replay_file_name(name: &str) -> Option<(String, Duration, (u16, u8, u8))> {
+    let stem = name.strip_suffix(REPLAY_EXTENSION)?.strip_suffix('.')?;
+    let mut parts = stem.split('-');
+    let difficulty = parts.next().filter(|d| !d.is_empty())?;
+    let time = Duration::from_millis(parts.next()?.parse().ok()?);
+    let date = parts
+        .next()
+        .filter(|d| d.len() == 8 && d.bytes().all(|b| b.is_ascii_digit()))?;
+    if let Some(copy) = parts.next() {
+        copy.parse::<usize>().ok()?;
+    }
+    if parts.next().is_some() {
+        return None;
+    }
+    let year = date[..4].parse().ok()?;
+    let month = date[4..6].parse().ok().filter(|m| (1..=12).contains(m))?;
+    let day = date[6..].parse().ok().filter(|d| (1..=31).contains(d))?;
+    Some((difficulty.to_string(), time, (year, month, day)))
+}
+
+/// Directory replays are saved to, under the user's data directory: `XDG_DATA_HOME`,
+/// `~/.local/share`, or `LOCALAPPDATA` on Windows, where `HOME` is usually unset.
+pub fn replay_dir() -> Option<PathBuf> {
+    let data_dir = env_dir("XDG_DATA_HOME")
+        .or_else(|| env_dir("HOME").map(|home| home.join(".local/share")))
+        .or_else(|| env_dir("LOCALAPPDATA"))?;
+    Some(data_dir.join("rust-sweeper").join("replays"))
+}
+
+/// Replays in `dir`, fastest first, skipping files with unrecognized names.
+/// Empty if the directory is missing or unreadable.
+pub fn list_replays_in(dir: &Path) -> Vec<ReplayMeta> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut replays: Vec<ReplayMeta> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let (difficulty, time, date) = parse_replay_file_name(path.file_name()?.to_str()?)?;
+            Some(ReplayMeta {
+                difficulty,
+                time,
+                date,
+                path,
+            })
+        })
+        .collect();
+    sort_replays(&mut replays);
+    replays
+}
+
+/// Order replays by time, breaking ties by the older date first.
+fn sort_replays(replays: &mut [ReplayMeta]) {
+    replays.sort_by(|a, b| a.time.cmp(&b.time).then(a.date.cmp(&b.date)));
+}
+
+/// Write a won game to `dir`, creating it if needed, and return the file's path.
+/// Existing replays are never overwritten: a win with the same name is saved as a numbered copy.
+pub fn save_replay_in(
+    dir: &Path,
+    replay: &Replay,
+    difficulty: &str,
+    time: Duration,
+    date: (u16, u8, u8),
+) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    for copy in 0.. {
+        let path = dir.join(replay_file_name(difficulty, time, date, copy));
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                file.write_all(replay.to_text().as_bytes())?;
+                return Ok(path);
+            }
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(error) => return Err(error),
+        }
+    }
+    unreachable!("a free replay file name exists")
+}
+
+pub fn load_replay(path: &Path) -> io::Result<Replay> {
+    let text = fs::read_to_string(path)?;
+    Replay::from_text(&text)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed replay"))
+}
+
+/// Current UTC date as `(year, month, day)`.
+pub fn today() -> (u16, u8, u8) {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since_epoch| since_epoch.as_secs());
+    civil_from_days((secs / 86_400) as i64)
+}
+
+/// Convert days since 1970-01-01 to a proleptic Gregorian date.
+fn civil_from_days(days: i64) -> (u16, u8, u8) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year as u16, month as u8, day as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_replay_file_name() {
+        let name = replay_file_name("Expert", Duration::from_millis(98_765), (2024, 2, 29), 0);
+        assert_eq!(name, "Expert-98765-20240229.replay");
+        assert_eq!(
+            parse_replay_file_name(&name),
+            Some((
+                "Expert".to_string(),
+                Duration::from_millis(98_765),
+                (2024, 2, 29)
+            ))
+        );
+
+        assert_eq!(parse_replay_file_name("Expert-98765-20240229.txt"), None);
+        assert_eq!(parse_replay_file_name("Expert-98765.replay"), None);
+        assert_eq!(parse_replay_file_name("-98765-20240229.replay"), None);
+        assert_eq!(parse_replay_file_name("Expert-fast-20240229.replay"), None);
+        assert_eq!(parse_replay_file_name("Expert-98765-2024229.replay"), None);
+        assert_eq!(parse_replay_file_name("Expert-98765-20241329.replay"), None);
+        assert_eq!(parse_replay_file_name("Expert-1-2-20240229.replay"), None);
+        assert_eq!(parse_replay_file_name("Expert-1-20240229-x.replay"), None);
+        assert_eq!(parse_replay_file_name("Expert-1-20240229-1-2.replay"), None);
+
+        let copy = replay_file_name("Expert", Duration::from_millis(98_765), (2024, 2, 29), 3);
+        assert_eq!(copy, "Expert-98765-20240229-3.replay");
+        assert_eq!(parse_replay_file_name(&copy), parse_replay_file_name(&name));
+    }
+
+    #[test]
+    fn test_sort_replays() {
+        let meta = |time: u64, date: (u16, u8, u8)| ReplayMeta {
+            difficulty: "Beginner".to_string(),
+            time: Duration::from_millis(time),
+            date,
+            path: PathBuf::new(),
+        };
+        let mut replays = vec![
+            meta(30_000, (2024, 1, 1)),
+            meta(9_000, (2024, 3, 1)),
+            meta(9_000, (2023, 12, 31)),
+            meta(120_000, (2022, 6, 15)),
+        ];
+        sort_replays(&mut replays);
+        assert_eq!(
+            replays
+                .iter()
+                .map(|r| (r.time.as_millis(), r.date))
+                .collect::<Vec<_>>(),
+            vec![
+                (9_000, (2023, 12, 31)),
+                (9_000, (2024, 3, 1)),
+                (30_000, (2024, 1, 1)),
+                (120_000, (2022, 6, 15)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_list_replays_in() {
+        let dir = std::env::temp_dir().join(format!("rust-sweeper-replays-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        assert!(list_replays_in(&dir).is_empty());
+
+        let replay = Replay {
+            board: vec![1, 2, 0xff],
+            steps: vec![
+                ReplayStep {
+                    at: Duration::ZERO,
+                    cursor: (3, 4),
+                    action: ReplayAction::Open,
+                },
+                ReplayStep {
+                    at: Duration::from_millis(1500),
+                    cursor: (0, -1),
+                    action: ReplayAction::Flag,
+                },
+            ],
+        };
+        let slow = Duration::from_secs(20);
+        let fast = Duration::from_secs(10);
+        save_replay_in(&dir, &replay, "Beginner", slow, (2024, 1, 2)).unwrap();
+        let path = save_replay_in(&dir, &replay, "Expert", fast, (2024, 1, 1)).unwrap();
+        fs::write(dir.join("notes.txt"), "not a replay").unwrap();
+
+        // The same difficulty, time and date does not overwrite the first replay
+        let other = Replay {
+            steps: Vec::new(),
+            ..replay.clone()
+        };
+        let copy = save_replay_in(&dir, &other, "Beginner", slow, (2024, 1, 2)).unwrap();
+        assert_eq!(load_replay(&copy).unwrap(), other);
+
+        let replays = list_replays_in(&dir);
+        assert_eq!(replays.len(), 3);
+        assert_eq!(replays[0].difficulty, "Expert");
+        assert_eq!(replays[0].path, path);
+        assert_eq!(replays[1].time, slow);
+        assert_eq!(replays[2].time, slow);
+        assert!(replays[1..]
+            .iter()
+            .any(|meta| load_replay(&meta.path).unwrap() == replay));
+        assert_eq!(load_replay(&path).unwrap(), replay);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_replay_from_malformed_text() {
+        assert_eq!(Replay::from_text(""), None);
+        assert_eq!(Replay::from_text("board 00\n"), None);
+        let header = format!("{REPLAY_HEADER}\n");
+        assert_eq!(Replay::from_text(&format!("{header}board 0\n")), None);
+        assert_eq!(
+            Replay::from_text(&format!("{header}board 00\n1 jump 0 0\n")),
+            None
+        );
+        assert_eq!(
+            Replay::from_text(&format!("{header}board 00\n1 open 0\n")),
+            None
+        );
+        assert_eq!(
+            Replay::from_text(&format!("{header}board 00\n")),
+            Some(Replay {
+                board: vec![0],
+                steps: vec![],
+            })
+        );
+    }
+
+    #[test]
+    fn test_civil_from_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(59), (1970, 3, 1));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(19_753), (2024, 1, 31));
+        assert_eq!(civil_from_days(20_742), (2026, 10, 16));
+    }
+}
@@ -0,0 +1,282 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::sweeper_controller::SweeperController;
+use super::sweeper_view::ViewOptions;
+use super::theme::Theme;
+use super::{env_dir, Difficulty, DEFAULT_DEBOUNCE};
+
+/// Preferences kept between runs in a `key = value` file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    /// Difficulty selected in the menu.
+    pub difficulty: Difficulty,
+    pub theme: Theme,
+    /// View toggles; `peek` and `safe_cells` are never saved.
+    pub view: ViewOptions,
+    pub auto_flag_on_win: bool,
+    pub max_reveal_per_call: Option<usize>,
+    pub flag_budget: Option<usize>,
+    /// Repeats of the same key within this interval are ignored.
+    pub debounce: Duration,
+    /// `key = value` lines with unknown keys, written back unchanged so that settings of newer
+    /// versions survive.
+    pub unknown: Vec<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            difficulty: Difficulty::Beginner,
+            theme: Theme::default(),
+            view: ViewOptions::default(),
+            auto_flag_on_win: true,
+            max_reveal_per_call: None,
+            flag_budget: None,
+            debounce: DEFAULT_DEBOUNCE,
+            unknown: Vec::new(),
+        }
+    }
+}
+
+impl Settings {
+    /// Default location of the settings file, under the user's config directory:
+    /// `XDG_CONFIG_HOME`, `~/.config`, or `APPDATA` on Windows.
+    pub fn path() -> Option<PathBuf> {
+        let config_dir = env_dir("XDG_CONFIG_HOME")
+            .or_else(|| env_dir("HOME").map(|home| home.join(".config")))
+            .or_else(|| env_dir("APPDATA"))?;
+        Some(config_dir.join("rust-sweeper").join("settings.conf"))
+    }
+
+    /// Settings from the default file, or the defaults if there is none.
+    pub fn load() -> Self {
+        Self::path().map_or_else(Self::default, |path| Self::load_from(&path))
+    }
+
+    /// Settings from `path` merged over the defaults. A missing or unreadable file gives the
+    /// defaults.
+    pub fn load_from(path: &Path) -> Self {
+        let mut settings = Self::default();
+        if let Ok(text) = fs::read_to_string(path) {
+            settings.merge(&text);
+        }
+        settings
+    }
+
+    /// Apply the `key = value` lines of `text`. Comments starting with `#` and invalid values
+    /// are ignored, and lines with unknown keys are kept in `unknown`, so older and newer files
+    /// still load.
+    pub fn merge(&mut self, text: &str) {
+        for line in text.lines() {
+            let line = line.trim();
+            if line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+            match key {
+                "difficulty" => {
+                    if let Some(difficulty) = Difficulty::ALL
+                        .into_iter()
+                        .find(|difficulty| difficulty.to_string().eq_ignore_ascii_case(value))
+                    {
+                        self.difficulty = difficulty;
+                    }
+                }
+                "theme" => {
+                    if let Some(theme) = Theme::by_name(value) {
+                        self.theme = theme;
+                    }
+                }
+                "assist" => set_parsed(&mut self.view.assist, value),
+                "rulers" => set_parsed(&mut self.view.show_rulers, value),
+                "fog" => set_parsed(&mut self.view.fog, value),
+                "auto_flag_on_win" => set_parsed(&mut self.auto_flag_on_win, value),
+                "max_reveal_per_call" => set_optional(&mut self.max_reveal_per_call, value),
+                "flag_budget" => set_optional(&mut self.flag_budget, value),
+                "debounce_ms" => {
+                    if let Ok(millis) = value.parse() {
+                        self.debounce = Duration::from_millis(millis);
+                    }
+                }
+                _ => {
+                    self.unknown.retain(|unknown| {
+                        unknown.split_once('=').map(|(other, _)| other.trim()) != Some(key)
+                    });
+                    self.unknown.push(line.to_string());
+                }
+            }
+        }
+    }
+
+    /// Text form read back by [`Self::merge`].
+    pub fn to_config_string(&self) -> String {
+        let optional = |value: Option<usize>| value.map_or("none".to_string(), |v| v.to_string());
+        let mut text = format!(
+            "# rust-sweeper settings\n\
+             difficulty = {}\n\
+             theme = {}\n\
+             assist = {}\n\
+             rulers = {}\n\
+             fog = {}\n\
+             auto_flag_on_win = {}\n\
+             max_reveal_per_call = {}\n\
+             flag_budget = {}\n\
+             debounce_ms = {}\n",
+            self.difficulty,
+            self.theme.name,
+            self.view.assist,
+            self.view.show_rulers,
+            self.view.fog,
+            self.auto_flag_on_win,
+            optional(self.max_reveal_per_call),
+            optional(self.flag_budget),
+            self.debounce.as_millis(),
+        );
+        for line in &self.unknown {
+            text.push_str(line);
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Write to the default file, creating its directory if needed. Does nothing without a
+    /// config directory.
+    pub fn save(&self) -> io::Result<()> {
+        match Self::path() {
+            Some(path) => self.save_to(&path),
+            None => Ok(()),
+        }
+    }
+
+    pub fn save_to(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_config_string())
+    }
+
+    /// Options the controller passes on to new games.
+    pub fn configure(&self, controller: &mut SweeperController) {
+        controller.auto_flag_on_win = self.auto_flag_on_win;
+        controller.max_reveal_per_call = self.max_reveal_per_call;
+        controller.flag_budget = self.flag_budget;
+    }
+}
+
+fn set_parsed<T: std::str::FromStr>(field: &mut T, value: &str) {
+    if let Ok(parsed) = value.parse() {
+        *field = parsed;
+    }
+}
+
+/// Parse a number, or `none` to clear the option.
+fn set_optional(field: &mut Option<usize>, value: &str) {
+    if value.eq_ignore_ascii_case("none") {
+        *field = None;
+    } else if let Ok(parsed) = value.parse() {
+        *field = Some(parsed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_partial() {
+        let mut settings = Settings::default();
+        settings.merge(
+            "# only a few keys\n\
+             theme = nerd\n\
+             assist = true\n\
+             flag_budget = 12\n",
+        );
+        assert_eq!(settings.theme, Theme::NERD);
+        assert!(settings.view.assist);
+        assert_eq!(settings.flag_budget, Some(12));
+        assert_eq!(settings.difficulty, Difficulty::Beginner);
+        assert!(settings.auto_flag_on_win);
+        assert_eq!(settings.debounce, DEFAULT_DEBOUNCE);
+
+        settings.merge("flag_budget = none\ndifficulty = expert");
+        assert_eq!(settings.flag_budget, None);
+        assert_eq!(settings.difficulty, Difficulty::Expert);
+    }
+
+    #[test]
+    fn test_merge_ignores_unknown_and_invalid() {
+        let mut settings = Settings::default();
+        settings.merge(
+            "color_scheme = solarized\n\
+             theme = missing\n\
+             fog = maybe\n\
+             debounce_ms = -5\n\
+             difficulty = impossible\n\
+             not a setting\n\
+             # rulers = true\n",
+        );
+        assert_eq!(
+            settings,
+            Settings {
+                unknown: vec!["color_scheme = solarized".to_string()],
+                ..Settings::default()
+            }
+        );
+
+        // A repeated unknown key keeps its last value
+        settings.merge("color_scheme=dark");
+        assert_eq!(settings.unknown, ["color_scheme=dark"]);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir =
+            std::env::temp_dir().join(format!("rust-sweeper-settings-{}", std::process::id()));
+        let path = dir.join("nested").join("settings.conf");
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(Settings::load_from(&path), Settings::default());
+
+        let settings = Settings {
+            difficulty: Difficulty::Intermediate,
+            theme: Theme::NERD,
+            view: ViewOptions {
+                assist: true,
+                peek: true,
+                safe_cells: true,
+                show_rulers: true,
+                fog: false,
+            },
+            auto_flag_on_win: false,
+            max_reveal_per_call: Some(64),
+            flag_budget: None,
+            debounce: Duration::from_millis(80),
+            unknown: vec!["keymap = vim".to_string()],
+        };
+        settings.save_to(&path).unwrap();
+        assert!(fs::read_to_string(&path)
+            .unwrap()
+            .ends_with("\nkeymap = vim\n"));
+
+        let loaded = Settings::load_from(&path);
+        assert!(!loaded.view.peek && !loaded.view.safe_cells);
+        assert_eq!(
+            loaded,
+            Settings {
+                view: ViewOptions {
+                    peek: false,
+                    safe_cells: false,
+                    ..settings.view
+                },
+                ..settings
+            }
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,76 +1,385 @@
+use std::collections::VecDeque;
 use std::time::Duration;
 
-use crate::model::sweeper::{GameState, SweeperGame};
+use super::replays::{Replay, ReplayAction, ReplayStep};
+use crate::model::sweeper::{
+    DecodeError, FlagResult, GameState, OpenResult, RevealToken, SolveProgress, SweeperGame,
+};
 
-/// Controller with cursor position.
+/// Parse a coordinate like `c7` into a cursor position.
+///
+/// Columns are letters starting from `a` (continuing with `aa`, `ab`, ... past `z`) and rows are
+/// numbers starting from 1. Returns `None` if the input is malformed or outside the board.
+pub fn parse_coordinate(input: &str, width: usize, height: usize) -> Option<(isize, isize)> {
+    let input = input.trim().to_ascii_lowercase();
+    let split = input.find(|c: char| !c.is_ascii_lowercase())?;
+    let (letters, digits) = input.split_at(split);
+    if letters.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let column = letters.bytes().try_fold(0usize, |acc, b| {
+        acc.checked_mul(26)?.checked_add((b - b'a') as usize + 1)
+    })?;
+    let row: usize = digits.parse().ok()?;
+    if column == 0 || column > width || row == 0 || row > height {
+        return None;
+    }
+    Some((column as isize - 1, row as isize - 1))
+}
+
+/// Letters naming column `x` in the notation read by [`parse_coordinate`].
+pub fn column_name(x: usize) -> String {
+    let mut name = Vec::new();
+    let mut rest = x + 1;
+    while rest > 0 {
+        rest -= 1;
+        name.push(b'a' + (rest % 26) as u8);
+        rest /= 26;
+    }
+    name.reverse();
+    String::from_utf8(name).expect("letters are ASCII")
+}
+
+/// Whether the whole seconds of `elapsed` differ from those last shown.
+fn second_changed(last_shown: Option<u64>, elapsed: Duration) -> bool {
+    last_shown != Some(elapsed.as_secs())
+}
+
+/// A game together with its own cursor and paused cascade.
+#[derive(Debug)]
+struct GameSlot {
+    game: SweeperGame,
+    cursor: (isize, isize),
+    pending_reveal: Option<RevealToken>,
+    /// Moves made so far, starting once the board is generated.
+    recording: Option<Replay>,
+    /// Moves still to be played back, if this game is a replay.
+    playback: Option<VecDeque<ReplayStep>>,
+}
+
+impl GameSlot {
+    fn new(game: SweeperGame) -> Self {
+        Self {
+            game,
+            cursor: (0, 0),
+            pending_reveal: None,
+            recording: None,
+            playback: None,
+        }
+    }
+
+    fn open(&mut self) -> Option<OpenResult> {
+        let (x, y) = self.cursor;
+        if self.game.state == GameState::NotRunning {
+            self.game.generate_board(x, y).ok()?;
+            self.game.start();
+            if self.playback.is_none() {
+                self.recording = Some(Replay {
+                    board: self.game.to_bytes(),
+                    steps: Vec::new(),
+                });
+            }
+        }
+        self.record(ReplayAction::Open);
+
+        // Finish any paused cascade before starting a new one
+        while let Some(token) = self.pending_reveal.take() {
+            self.pending_reveal = self.game.resume_reveal(token).pending;
+        }
+
+        let result = self.game.open_with_result(x, y);
+        self.pending_reveal = result.pending.clone();
+        Some(result)
+    }
+
+    fn flag(&mut self) -> FlagResult {
+        let result = self.game.flag(self.cursor.0, self.cursor.1);
+        if result != FlagResult::Ignored {
+            self.record(ReplayAction::Flag);
+        }
+        result
+    }
+
+    fn record(&mut self, action: ReplayAction) {
+        if let Some(recording) = self.recording.as_mut() {
+            recording.steps.push(ReplayStep {
+                at: self.game.get_elapsed_time(),
+                cursor: self.cursor,
+                action,
+            });
+        }
+    }
+
+    /// Apply the replayed moves that are due by now, returning whether there were any.
+    fn advance_playback(&mut self) -> bool {
+        let mut advanced = false;
+        let elapsed = self.game.get_elapsed_time();
+        while let Some(step) = self
+            .playback
+            .as_mut()
+            .filter(|steps| steps.front().is_some_and(|step| step.at <= elapsed))
+            .and_then(|steps| steps.pop_front())
+        {
+            advanced = true;
+            self.cursor = step.cursor;
+            match step.action {
+                ReplayAction::Open => {
+                    self.open();
+                }
+                ReplayAction::Flag => {
+                    self.flag();
+                }
+            }
+        }
+        advanced
+    }
+}
+
+/// Controller holding one or more games, each with its own cursor position.
+/// Single-game methods act on the active game.
 #[derive(Debug)]
 pub struct SweeperController {
-    pub game: Option<SweeperGame>,
-    pub cursor: (isize, isize),
+    games: Vec<GameSlot>,
+    active: usize,
+    /// Flag remaining bombs when a game is won.
+    pub auto_flag_on_win: bool,
+    /// Cap on cells revealed per open; the rest of the cascade continues on [`Self::tick`].
+    pub max_reveal_per_call: Option<usize>,
+    /// Maximum number of flags placed at once in new games.
+    pub flag_budget: Option<usize>,
+    /// Whole seconds of the active game's time when last queried by the app.
+    last_shown_second: Option<u64>,
+}
+
+impl Default for SweeperController {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SweeperController {
     pub fn new() -> Self {
         Self {
-            game: None,
-            cursor: (0, 0),
+            games: Vec::new(),
+            active: 0,
+            auto_flag_on_win: true,
+            max_reveal_per_call: None,
+            flag_budget: None,
+            last_shown_second: None,
         }
     }
 
+    /// Replace the active game with a new one, or add it if there is none.
     pub fn start_game(&mut self, width: usize, height: usize, bomb_count: usize) {
-        self.game = Some(SweeperGame::new(width, height, bomb_count));
-        self.cursor = (0, 0);
+        let slot = self.new_slot(width, height, bomb_count);
+        match self.games.get_mut(self.active) {
+            Some(active) => *active = slot,
+            None => self.add_slot(slot),
+        }
     }
 
-    pub fn is_running(&self) -> bool {
-        match self.game {
-            Some(ref game) => {
-                game.state == GameState::NotRunning || game.state == GameState::Running
+    /// Add a new game alongside the existing ones and make it active.
+    pub fn add_game(&mut self, width: usize, height: usize, bomb_count: usize) {
+        let slot = self.new_slot(width, height, bomb_count);
+        self.add_slot(slot);
+    }
+
+    /// Make the game at `index` active. Returns false if there is no such game.
+    pub fn switch_to(&mut self, index: usize) -> bool {
+        if index < self.games.len() {
+            self.active = index;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Cycle the active game by `offset`, wrapping around.
+    pub fn switch_by(&mut self, offset: isize) {
+        if !self.games.is_empty() {
+            let len = self.games.len() as isize;
+            self.active = (self.active as isize + offset).rem_euclid(len) as usize;
+        }
+    }
+
+    /// Close the active game, activating its neighbor.
+    pub fn close_game(&mut self) {
+        if self.active < self.games.len() {
+            self.games.remove(self.active);
+            self.active = self.active.min(self.games.len().saturating_sub(1));
+        }
+    }
+
+    pub fn game(&self) -> Option<&SweeperGame> {
+        self.slot().map(|slot| &slot.game)
+    }
+
+    pub fn game_mut(&mut self) -> Option<&mut SweeperGame> {
+        self.slot_mut().map(|slot| &mut slot.game)
+    }
+
+    pub fn game_count(&self) -> usize {
+        self.games.len()
+    }
+
+    pub fn active_index(&self) -> usize {
+        self.active
+    }
+
+    /// Cursor of the active game.
+    pub fn cursor(&self) -> (isize, isize) {
+        self.slot().map_or((0, 0), |slot| slot.cursor)
+    }
+
+    /// Move the cursor of the active game, if the position is on the board.
+    pub fn set_cursor(&mut self, (x, y): (isize, isize)) {
+        if let Some(slot) = self.slot_mut() {
+            if slot.game.is_valid_coordinate(x, y) {
+                slot.cursor = (x, y);
             }
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        match self.game() {
+            Some(game) => game.state.is_active(),
             None => false,
         }
     }
 
     pub fn get_elapsed_time(&self) -> Duration {
-        match self.game {
-            Some(ref game) => game.get_elapsed_time(),
+        match self.game() {
+            Some(game) => game.get_elapsed_time(),
             None => Duration::ZERO,
         }
     }
 
-    pub fn open(&mut self) {
-        let (x, y) = self.cursor;
+    /// Open the cell under the cursor, returning `None` if there is no game.
+    pub fn open(&mut self) -> Option<OpenResult> {
+        self.slot_mut()?.open()
+    }
 
-        if let Some(ref mut game) = self.game {
-            if game.state == GameState::NotRunning {
-                game.generate_board(x, y);
-                game.start();
+    /// Continue paused cascades and replays, if any. Returns whether any game changed.
+    pub fn tick(&mut self) -> bool {
+        let mut changed = false;
+        for slot in self.games.iter_mut() {
+            if let Some(token) = slot.pending_reveal.take() {
+                slot.pending_reveal = slot.game.resume_reveal(token).pending;
+                changed = true;
             }
-            game.open(x, y);
+            changed |= slot.advance_playback();
         }
+        changed
     }
 
-    pub fn flag(&mut self) {
-        let (x, y) = self.cursor;
-        if let Some(ref mut game) = self.game {
-            game.flag(x, y);
+    /// Whether the displayed seconds of the active game changed since the last call.
+    pub fn seconds_changed_since_last_draw(&mut self) -> bool {
+        let elapsed = self.get_elapsed_time();
+        let changed = second_changed(self.last_shown_second, elapsed);
+        self.last_shown_second = Some(elapsed.as_secs());
+        changed
+    }
+
+    pub fn flag(&mut self) -> FlagResult {
+        match self.slot_mut() {
+            Some(slot) => slot.flag(),
+            None => FlagResult::Ignored,
+        }
+    }
+
+    /// Apply every safe deduction on the active game. Assisted games are no longer recorded.
+    pub fn solve(&mut self) -> SolveProgress {
+        let Some(slot) = self.slot_mut() else {
+            return SolveProgress::default();
+        };
+        while let Some(token) = slot.pending_reveal.take() {
+            slot.pending_reveal = slot.game.resume_reveal(token).pending;
+        }
+        let progress = slot.game.solve_all();
+        if !progress.is_stuck() {
+            slot.recording = None;
+        }
+        progress
+    }
+
+    /// Replace the active game with a playback of `replay`, advanced by [`Self::tick`].
+    ///
+    /// The playback ignores the current options such as `flag_budget`: the recorded moves
+    /// already went through the options they were played with, and different ones could
+    /// reject a move.
+    pub fn play_replay(&mut self, replay: Replay) -> Result<(), DecodeError> {
+        let mut game = SweeperGame::from_bytes(&replay.board)?;
+        game.start();
+        let mut slot = GameSlot::new(game);
+        slot.playback = Some(replay.steps.into());
+        match self.games.get_mut(self.active) {
+            Some(active) => *active = slot,
+            None => self.add_slot(slot),
         }
+        Ok(())
+    }
+
+    /// Whether the active game is a replay rather than one being played.
+    pub fn is_replaying(&self) -> bool {
+        self.slot().is_some_and(|slot| slot.playback.is_some())
+    }
+
+    /// Take the recordings of won games along with their times. Each is returned once.
+    pub fn take_won_replays(&mut self) -> Vec<(Replay, Duration)> {
+        self.games
+            .iter_mut()
+            .filter(|slot| slot.game.state == GameState::Win)
+            .filter_map(|slot| Some((slot.recording.take()?, slot.game.get_elapsed_time())))
+            .collect()
     }
 
     pub fn move_cursor(&mut self, dx: isize, dy: isize) {
-        if let Some(ref game) = self.game {
-            let (x, y) = self.cursor;
-            let x = (x as isize + dx).clamp(0, game.get_width() as isize - 1);
-            let y = (y as isize + dy).clamp(0, game.get_height() as isize - 1);
-            self.cursor = (x, y);
+        if let Some(slot) = self.slot_mut() {
+            let (x, y) = slot.cursor;
+            let x = (x + dx).clamp(0, slot.game.get_width() as isize - 1);
+            let y = (y + dy).clamp(0, slot.game.get_height() as isize - 1);
+            slot.cursor = (x, y);
         }
     }
 
     pub fn resign(&mut self) {
-        if let Some(ref mut game) = self.game {
-            game.state = crate::model::sweeper::GameState::Lose;
+        if let Some(game) = self.game_mut() {
+            game.resign();
         }
     }
+
+    /// Give up on the current game without revealing the bombs.
+    pub fn abandon(&mut self) {
+        if let Some(game) = self.game_mut() {
+            game.abandon();
+        }
+    }
+
+    fn new_slot(&self, width: usize, height: usize, bomb_count: usize) -> GameSlot {
+        let mut game = SweeperGame::new(width, height, bomb_count);
+        self.configure(&mut game);
+        GameSlot::new(game)
+    }
+
+    fn configure(&self, game: &mut SweeperGame) {
+        game.auto_flag_on_win = self.auto_flag_on_win;
+        game.max_reveal_per_call = self.max_reveal_per_call;
+        game.flag_budget = self.flag_budget;
+    }
+
+    fn add_slot(&mut self, slot: GameSlot) {
+        self.games.push(slot);
+        self.active = self.games.len() - 1;
+    }
+
+    fn slot(&self) -> Option<&GameSlot> {
+        self.games.get(self.active)
+    }
+
+    fn slot_mut(&mut self) -> Option<&mut GameSlot> {
+        self.games.get_mut(self.active)
+    }
 }
 
 #[cfg(test)]
@@ -78,8 +387,8 @@ mod tests {
     #[test]
     fn test_new_controller() {
         let controller = super::SweeperController::new();
-        assert_eq!(controller.cursor, (0, 0));
-        assert!(controller.game.is_none());
+        assert_eq!(controller.cursor(), (0, 0));
+        assert!(controller.game().is_none());
     }
 
     #[test]
@@ -88,14 +397,199 @@ mod tests {
         controller.start_game(4, 4, 0);
 
         controller.move_cursor(1, 1);
-        assert_eq!(controller.cursor, (1, 1));
+        assert_eq!(controller.cursor(), (1, 1));
         controller.move_cursor(-1, -1);
-        assert_eq!(controller.cursor, (0, 0));
+        assert_eq!(controller.cursor(), (0, 0));
         controller.move_cursor(-1, -1); // should not move out of bounds
-        assert_eq!(controller.cursor, (0, 0));
+        assert_eq!(controller.cursor(), (0, 0));
         controller.move_cursor(3, 3);
-        assert_eq!(controller.cursor, (3, 3));
+        assert_eq!(controller.cursor(), (3, 3));
         controller.move_cursor(1, 1); // should not move out of bounds
-        assert_eq!(controller.cursor, (3, 3));
+        assert_eq!(controller.cursor(), (3, 3));
+    }
+
+    #[test]
+    fn test_open_without_game() {
+        let mut controller = super::SweeperController::new();
+        assert!(controller.open().is_none());
+    }
+
+    #[test]
+    fn test_tick_resumes_capped_reveal() {
+        let mut controller = super::SweeperController::new();
+        controller.max_reveal_per_call = Some(10);
+        controller.start_game(10, 10, 0);
+
+        let result = controller.open().unwrap();
+        assert!(result.revealed <= 10);
+        assert!(result.pending.is_some());
+
+        for _ in 0..100 {
+            controller.tick();
+        }
+        let game = controller.game().unwrap();
+        assert_eq!(game.num_revealed, 100);
+        assert_eq!(game.state, super::GameState::Win);
+    }
+
+    #[test]
+    fn test_parse_coordinate() {
+        use super::parse_coordinate;
+
+        assert_eq!(parse_coordinate("a1", 10, 10), Some((0, 0)));
+        assert_eq!(parse_coordinate("c7", 10, 10), Some((2, 6)));
+        assert_eq!(parse_coordinate(" C7 ", 10, 10), Some((2, 6)));
+        assert_eq!(parse_coordinate("j10", 10, 10), Some((9, 9)));
+        assert_eq!(parse_coordinate("z1", 30, 16), Some((25, 0)));
+        assert_eq!(parse_coordinate("ad16", 30, 16), Some((29, 15)));
+
+        // Out of range
+        assert_eq!(parse_coordinate("k1", 10, 10), None);
+        assert_eq!(parse_coordinate("a11", 10, 10), None);
+        assert_eq!(parse_coordinate("a0", 10, 10), None);
+        assert_eq!(parse_coordinate("ae1", 30, 16), None);
+
+        // Malformed
+        assert_eq!(parse_coordinate("", 10, 10), None);
+        assert_eq!(parse_coordinate("a", 10, 10), None);
+        assert_eq!(parse_coordinate("7", 10, 10), None);
+        assert_eq!(parse_coordinate("7c", 10, 10), None);
+        assert_eq!(parse_coordinate("c7x", 10, 10), None);
+        assert_eq!(parse_coordinate("c-1", 10, 10), None);
+        assert_eq!(parse_coordinate("zzzzzzzzzzzzzzzz1", 10, 10), None);
+    }
+
+    #[test]
+    fn test_column_name() {
+        use super::{column_name, parse_coordinate};
+
+        assert_eq!(column_name(0), "a");
+        assert_eq!(column_name(25), "z");
+        assert_eq!(column_name(26), "aa");
+        assert_eq!(column_name(29), "ad");
+        assert_eq!(column_name(701), "zz");
+        assert_eq!(column_name(702), "aaa");
+
+        for x in 0..800 {
+            let input = format!("{}1", column_name(x));
+            assert_eq!(parse_coordinate(&input, 800, 1), Some((x as isize, 0)));
+        }
+    }
+
+    #[test]
+    fn test_multiple_games() {
+        let mut controller = super::SweeperController::new();
+        controller.start_game(10, 10, 0);
+        controller.move_cursor(2, 3);
+        controller.open();
+        assert_eq!(controller.game().unwrap().num_revealed, 100);
+
+        controller.add_game(8, 8, 0);
+        assert_eq!(controller.game_count(), 2);
+        assert_eq!(controller.active_index(), 1);
+        assert_eq!(controller.cursor(), (0, 0));
+        assert_eq!(controller.game().unwrap().num_revealed, 0);
+        controller.flag();
+
+        assert!(controller.switch_to(0));
+        assert_eq!(controller.cursor(), (2, 3));
+        assert_eq!(controller.game().unwrap().num_revealed, 100);
+        assert_eq!(controller.game().unwrap().num_flags, 0);
+        assert!(!controller.switch_to(2));
+
+        controller.switch_by(-1);
+        assert_eq!(controller.active_index(), 1);
+        assert_eq!(controller.game().unwrap().num_revealed, 0);
+        assert_eq!(controller.game().unwrap().num_flags, 1);
+
+        controller.close_game();
+        assert_eq!(controller.game_count(), 1);
+        assert_eq!(controller.active_index(), 0);
+        assert_eq!(controller.cursor(), (2, 3));
+
+        controller.close_game();
+        assert!(controller.game().is_none());
+        assert!(controller.open().is_none());
+    }
+
+    #[test]
+    fn test_record_and_play_replay() {
+        let mut controller = super::SweeperController::new();
+        controller.start_game(3, 1, 1);
+        controller.set_cursor((1, 0));
+        controller.open();
+        assert!(controller.take_won_replays().is_empty());
+
+        // Finish the game with the bomb at one of the ends
+        let game = controller.game().unwrap();
+        let bomb_x = if game.get_cell(0, 0).unwrap().is_bomb {
+            0
+        } else {
+            2
+        };
+        controller.set_cursor((bomb_x, 0));
+        controller.flag();
+        controller.set_cursor((2 - bomb_x, 0));
+        controller.open();
+        assert_eq!(controller.game().unwrap().state, super::GameState::Win);
+
+        let mut won = controller.take_won_replays();
+        assert_eq!(won.len(), 1);
+        assert!(controller.take_won_replays().is_empty());
+        let (mut replay, _) = won.pop().unwrap();
+        assert_eq!(replay.steps.len(), 3);
+
+        // Make every move due on the first tick
+        for step in replay.steps.iter_mut() {
+            step.at = std::time::Duration::ZERO;
+        }
+
+        // Options that would reject the recorded flag don't apply to the playback
+        controller.flag_budget = Some(0);
+        controller.max_reveal_per_call = Some(1);
+
+        controller.play_replay(replay).unwrap();
+        assert!(controller.is_replaying());
+        assert_eq!(controller.game().unwrap().num_revealed, 0);
+        controller.tick();
+        let game = controller.game().unwrap();
+        assert_eq!(game.state, super::GameState::Win);
+        assert_eq!(game.num_flags, 1);
+        assert!(controller.take_won_replays().is_empty());
+
+        assert!(controller.play_replay(super::Replay::default()).is_err());
+    }
+
+    #[test]
+    fn test_second_changed() {
+        use super::second_changed;
+        use std::time::Duration;
+
+        let ms = Duration::from_millis;
+        assert!(second_changed(None, Duration::ZERO));
+        assert!(!second_changed(Some(0), ms(0)));
+        assert!(!second_changed(Some(0), ms(999)));
+        assert!(second_changed(Some(0), ms(1000)));
+        assert!(!second_changed(Some(4), ms(4001)));
+        assert!(!second_changed(Some(4), ms(4999)));
+        assert!(second_changed(Some(4), ms(5000)));
+        assert!(second_changed(Some(4), ms(7250)));
+        // A reset or switched game shows a smaller time
+        assert!(second_changed(Some(4), ms(300)));
+    }
+
+    #[test]
+    fn test_seconds_changed_since_last_draw() {
+        let mut controller = super::SweeperController::new();
+        assert!(controller.seconds_changed_since_last_draw());
+        assert!(!controller.seconds_changed_since_last_draw());
+
+        controller.start_game(4, 4, 0);
+        assert!(!controller.seconds_changed_since_last_draw());
+        let game = controller.game_mut().unwrap();
+        game.state = super::GameState::Win;
+        game.total_time = std::time::Duration::from_secs(3);
+        assert!(controller.seconds_changed_since_last_draw());
+        assert!(!controller.seconds_changed_since_last_draw());
     }
 }
@@ -1,74 +1,673 @@
 use ratatui::{
     style::{Color, Stylize},
-    text::{Line, Text},
-    widgets::Paragraph,
+    text::{Line, Span, Text},
 };
 
-use crate::model::sweeper::{GameState, SweeperGame};
-
-const NUM_TEXTS: [&str; 9] = ["　", "１", "２", "３", "４", "５", "６", "７", "８"];
-const NUM_COLORS: [&Color; 9] = [
-    &Color::Black,
-    &Color::Blue,
-    &Color::Green,
-    &Color::Red,
-    &Color::Magenta,
-    &Color::Cyan,
-    &Color::Yellow,
-    &Color::White,
-    &Color::Gray,
-];
-const BOMB_TEXT: &str = "💣";
-const FLAG_TEXT: &str = "🚩";
-const EMPTY_TEXT: &str = "　";
-
-pub fn draw_game(game: &SweeperGame, cursor: (isize, isize)) -> Paragraph {
+use super::sweeper_controller::column_name;
+use super::theme::Theme;
+use crate::model::sweeper::{CellDisplay, GameState, SweeperGame};
+
+/// Faint assist marker on a hidden cursor cell that is provably safe. Unlike
+/// [`ViewOptions::safe_cells`] it only shows what the solver deduces from the revealed numbers.
+const SAFE_HINT: &str = "✓ ";
+
+const BAR_FULL: char = '█';
+const BAR_EMPTY: char = '░';
+
+/// Progress bar of `width` characters showing placed flags out of the bomb count.
+pub fn flag_bar(num_flags: usize, num_bombs: usize, width: usize) -> String {
+    let ratio = if num_bombs == 0 {
+        1.0
+    } else {
+        (num_flags as f64 / num_bombs as f64).clamp(0.0, 1.0)
+    };
+    let filled = (ratio * width as f64).round() as usize;
+    (0..width)
+        .map(|i| if i < filled { BAR_FULL } else { BAR_EMPTY })
+        .collect()
+}
+
+/// Color of the flag bar; green when flags match bombs and red when there are too many.
+fn flag_bar_color(num_flags: usize, num_bombs: usize) -> Color {
+    if num_flags > num_bombs {
+        Color::Red
+    } else if num_flags == num_bombs {
+        Color::Green
+    } else if num_flags * 2 >= num_bombs {
+        Color::Yellow
+    } else {
+        Color::White
+    }
+}
+
+/// Optional rendering features of the game view.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ViewOptions {
+    /// Tutorial aid showing information that would otherwise be hidden.
+    pub assist: bool,
+    /// Debug aid marking hidden bombs while the game is running.
+    pub peek: bool,
+    /// Tutorial aid highlighting every hidden safe cell while the game is running. It gives
+    /// the layout away, so unlike `assist` it is never saved.
+    pub safe_cells: bool,
+    /// Show column numbers above and row numbers left of the board.
+    pub show_rulers: bool,
+    /// Give hidden and revealed cells the same background so only the glyphs differ.
+    pub fog: bool,
+}
+
+/// Width and height needed to draw `game` with [`draw_game`] at any point of play, including
+/// the surrounding border.
+pub fn required_size(mut game: SweeperGame, options: ViewOptions, theme: &Theme) -> (usize, usize) {
+    // A finished game shows the most header lines
+    game.state = GameState::Lose;
+    let text = draw_game(&game, (0, 0), options, theme, 0).into_text();
+    (text.width() + 2, text.height() + 2)
+}
+
+/// Width of the row ruler: the widest row number plus a space.
+fn row_ruler_width(height: usize) -> usize {
+    height.to_string().len() + 1
+}
+
+/// Lines taken by the column ruler: the length of the longest column name.
+fn column_ruler_height(width: usize) -> usize {
+    column_name(width.saturating_sub(1)).len()
+}
+
+/// Column names as typed in go-to, aligned with the two-column cells. Names longer than one
+/// letter are written downwards, one letter per line.
+fn column_ruler(width: usize, height: usize) -> Vec<Line<'static>> {
+    let lines = column_ruler_height(width);
+    let names: Vec<String> = (0..width)
+        .map(|x| format!("{:>lines$}", column_name(x)))
+        .collect();
+    (0..lines)
+        .map(|i| {
+            let mut ruler = " ".repeat(row_ruler_width(height));
+            for name in &names {
+                ruler.push_str(&format!("{:>2}", &name[i..=i]));
+            }
+            Line::from(ruler.fg(Color::DarkGray))
+        })
+        .collect()
+}
+
+/// Number of frames in the game over animations.
+pub const ANIMATION_FRAMES: usize = 5;
+/// Ticks each animation frame stays on screen.
+pub const TICKS_PER_FRAME: usize = 2;
+
+const EXPLOSION_FRAMES: [&str; ANIMATION_FRAMES] = ["·", "*", "✶", "✹", "✺"];
+
+/// Animation frame to show `ticks` after the game ended. The last frame is held.
+pub fn animation_frame(ticks: usize) -> usize {
+    (ticks / TICKS_PER_FRAME).min(ANIMATION_FRAMES - 1)
+}
+
+/// Animation line under the result: flags cascading in on a win, an explosion on a loss.
+fn game_over_line(state: GameState, ticks: usize, theme: &Theme) -> Option<Line<'static>> {
+    let frame = animation_frame(ticks);
+    match state {
+        GameState::Win => Some(Line::from(theme.flag.repeat(frame + 1))),
+        GameState::Lose => Some(Line::from(
+            EXPLOSION_FRAMES[frame]
+                .repeat(frame + 1)
+                .bold()
+                .fg(Color::Red),
+        )),
+        _ => None,
+    }
+}
+
+/// Styled content of a game, kept apart so it can be inspected before it is drawn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoardView {
+    /// Status lines, the flag bar and, with rulers on, the column ruler.
+    pub header: Vec<Line<'static>>,
+    /// Row labels, empty unless rulers are on.
+    pub row_labels: Vec<Span<'static>>,
+    /// Cell spans, indexed by row then column.
+    pub cells: Vec<Vec<Span<'static>>>,
+}
+
+impl BoardView {
+    /// Header followed by one line per row.
+    pub fn into_text(self) -> Text<'static> {
+        let mut labels = self.row_labels.into_iter();
+        let rows = self.cells.into_iter().map(|row| {
+            let mut line = Line::from(row);
+            if let Some(label) = labels.next() {
+                line.spans.insert(0, label);
+            }
+            line
+        });
+        Text::from_iter(self.header.into_iter().chain(rows))
+    }
+}
+
+/// `animation_ticks` counts the ticks since the game ended and is ignored before that.
+pub fn draw_game(
+    game: &SweeperGame,
+    cursor: (isize, isize),
+    options: ViewOptions,
+    theme: &Theme,
+    animation_ticks: usize,
+) -> BoardView {
     let time_text = format!("Time: {}", game.get_elapsed_time().as_secs());
     let time_line = Line::from(time_text.bold().fg(Color::White));
 
     let mine_count_text = format!("Mines: {}", game.num_bombs);
     let mine_count_line = Line::from(mine_count_text.bold().fg(Color::White));
 
-    let bomb_count_line = if game.state == GameState::Win {
+    // Rounded down, so 100% only shows once the board is cleared
+    let cleared = (game.reveal_progress() * 100.0).floor();
+    let cleared_text = format!("Cleared: {cleared}%");
+    let cleared_line = Line::from(cleared_text.bold().fg(Color::White));
+
+    let bomb_count_line = if game.state == GameState::Win && game.won_with_perfect_flags() {
+        Line::from("You Win! ⭐".bold().fg(Color::Green))
+    } else if game.state == GameState::Win {
         Line::from("You Win!".bold().fg(Color::Green))
     } else if game.state == GameState::Lose {
         Line::from("You Lose!".bold().fg(Color::Red))
+    } else if game.state == GameState::Abandoned {
+        Line::from("Abandoned".bold().fg(Color::Yellow))
     } else {
-        let bomb_count_text = format!("Remaining: {}", game.num_bombs - game.num_flags);
+        let remaining = game.num_bombs as isize - game.num_flags as isize;
+        let bomb_count_text = format!("Remaining: {remaining}");
         Line::from(bomb_count_text.bold().fg(Color::White))
     };
 
-    let board_text = Text::from_iter(game.cell_row_iter().enumerate().map(|(y, row)| {
-        Line::from_iter(row.iter().enumerate().map(|(x, cell)| {
-            let text = if game.state == GameState::Lose && cell.is_bomb {
-                BOMB_TEXT.into()
-            } else if cell.is_revealed {
-                NUM_TEXTS[cell.mine_count as usize]
-                    .bold()
-                    .fg(*NUM_COLORS[cell.mine_count as usize])
-            } else if cell.is_flagged {
-                FLAG_TEXT.into()
-            } else {
-                EMPTY_TEXT.into()
-            };
-
-            if (game.state == GameState::NotRunning || game.state == GameState::Running)
-                && (x as isize, y as isize) == cursor
-            {
-                text.on_black()
-            } else if cell.is_revealed {
-                text.on_dark_gray()
-            } else {
-                text.on_gray()
+    let flags_left_line = game
+        .flags_left()
+        .map(|flags_left| Line::from(format!("Flags left: {flags_left}").bold().fg(Color::White)));
+
+    let assist_line = options.assist.then(|| {
+        let safe_cells = game.safe_cells_remaining_coords().len();
+        let unflagged = game.true_remaining_bombs();
+        let undecidable = game.undecidable_count();
+        Line::from(
+            format!(
+                "Safe cells left: {safe_cells} Unflagged mines: {unflagged} Undecidable: {undecidable}"
+            )
+            .fg(Color::LightGreen),
+        )
+    });
+
+    let flag_bar_text = flag_bar(game.num_flags, game.num_bombs, game.get_width() * 2);
+    let flag_bar_line =
+        Line::from(flag_bar_text.fg(flag_bar_color(game.num_flags, game.num_bombs)));
+
+    let ruler_width = row_ruler_width(game.get_height());
+    let row_labels = if options.show_rulers {
+        (0..game.get_height())
+            .map(|y| format!("{:>width$} ", y + 1, width = ruler_width - 1).fg(Color::DarkGray))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let cells = (0..game.get_height() as isize)
+        .map(|y| {
+            (0..game.get_width() as isize)
+                .map(|x| cell_span(game, x, y, cursor, options, theme))
+                .collect()
+        })
+        .collect();
+
+    let mut header = vec![time_line, mine_count_line, cleared_line, bomb_count_line];
+    header.extend(game_over_line(game.state, animation_ticks, theme));
+    header.extend(flags_left_line);
+    header.extend(assist_line);
+    header.push(flag_bar_line);
+    if options.show_rulers {
+        header.extend(column_ruler(game.get_width(), game.get_height()));
+    }
+
+    BoardView {
+        header,
+        row_labels,
+        cells,
+    }
+}
+
+/// Styled glyph for the cell at the given coordinate.
+fn cell_span(
+    game: &SweeperGame,
+    x: isize,
+    y: isize,
+    cursor: (isize, isize),
+    options: ViewOptions,
+    theme: &Theme,
+) -> Span<'static> {
+    let Some(cell) = game.get_cell(x, y) else {
+        return Span::raw(theme.empty);
+    };
+
+    let text = match cell.display_kind(game.state == GameState::Lose) {
+        CellDisplay::Bomb => theme.bomb.into(),
+        CellDisplay::Revealed(count)
+            if game.state == GameState::Running && game.is_number_satisfied(x, y) =>
+        {
+            theme.num_glyph(count).dim().fg(theme.num_color(count))
+        }
+        CellDisplay::Revealed(count) => theme.num_glyph(count).bold().fg(theme.num_color(count)),
+        CellDisplay::Flagged => theme.flag.into(),
+        CellDisplay::Hidden if options.peek && game.state == GameState::Running && cell.is_bomb => {
+            theme.bomb.dim()
+        }
+        CellDisplay::Hidden
+            if options.assist && (x, y) == cursor && game.is_cursor_provably_safe(x, y) =>
+        {
+            SAFE_HINT.dim()
+        }
+        CellDisplay::Hidden => theme.empty.into(),
+    };
+
+    let detonated = game.last_opened == game.board.index(x, y);
+    if game.state.is_active() && (x, y) == cursor {
+        text.on_black()
+    } else if detonated {
+        text.on_red()
+    } else if options.fog || cell.is_revealed {
+        text.on_dark_gray()
+    } else if options.safe_cells && game.state == GameState::Running && !cell.is_bomb {
+        text.on_light_green()
+    } else {
+        text.on_gray()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flag_bar() {
+        assert_eq!(flag_bar(0, 10, 10), "░░░░░░░░░░");
+        assert_eq!(flag_bar(5, 10, 10), "█████░░░░░");
+        assert_eq!(flag_bar(10, 10, 10), "██████████");
+        assert_eq!(flag_bar(15, 10, 10), "██████████");
+        assert_eq!(flag_bar(0, 0, 4), "████");
+
+        assert_eq!(flag_bar_color(0, 10), Color::White);
+        assert_eq!(flag_bar_color(5, 10), Color::Yellow);
+        assert_eq!(flag_bar_color(10, 10), Color::Green);
+        assert_eq!(flag_bar_color(15, 10), Color::Red);
+    }
+
+    #[test]
+    fn test_satisfied_number_dimmed() {
+        use ratatui::style::Modifier;
+
+        let mut game = SweeperGame::new(3, 3, 0);
+
+        // Layout
+        // x 1 .
+        // 1 1 .
+        // . . x
+        game.place_bombs(&[(0, 0), (2, 2)]).unwrap();
+        game.start();
+        game.open(1, 0);
+        game.open(0, 1);
+        game.flag(0, 0);
+
+        let cursor = (-1, -1);
+        assert!(game.is_number_satisfied(1, 0));
+        let satisfied = cell_span(&game, 1, 0, cursor, ViewOptions::default(), &Theme::CLASSIC);
+        assert!(satisfied.style.add_modifier.contains(Modifier::DIM));
+        assert!(!satisfied.style.add_modifier.contains(Modifier::BOLD));
+
+        game.open(1, 1);
+        assert!(!game.is_number_satisfied(1, 1));
+        let unsatisfied = cell_span(&game, 1, 1, cursor, ViewOptions::default(), &Theme::CLASSIC);
+        assert!(!unsatisfied.style.add_modifier.contains(Modifier::DIM));
+        assert!(unsatisfied.style.add_modifier.contains(Modifier::BOLD));
+    }
+
+    #[test]
+    fn test_highlight_safe_cells() {
+        let mut game = SweeperGame::new(3, 1, 0);
+        game.place_bombs(&[(0, 0)]).unwrap();
+        game.start();
+
+        let cursor = (-1, -1);
+        let background = |x, options| {
+            cell_span(&game, x, 0, cursor, options, &Theme::CLASSIC)
+                .style
+                .bg
+        };
+        let safe_cells = ViewOptions {
+            safe_cells: true,
+            ..Default::default()
+        };
+        assert_eq!(background(1, safe_cells), Some(Color::LightGreen));
+        assert_eq!(background(0, safe_cells), Some(Color::Gray));
+        assert_eq!(background(1, ViewOptions::default()), Some(Color::Gray));
+
+        // Assist alone keeps the layout hidden
+        let assist = ViewOptions {
+            assist: true,
+            ..Default::default()
+        };
+        assert_eq!(background(1, assist), Some(Color::Gray));
+    }
+
+    #[test]
+    fn test_peek_marks_hidden_bombs() {
+        use ratatui::style::Modifier;
+
+        let mut game = SweeperGame::new(3, 1, 0);
+        game.place_bombs(&[(0, 0)]).unwrap();
+        game.start();
+
+        let cursor = (-1, -1);
+        let peek = ViewOptions {
+            peek: true,
+            ..Default::default()
+        };
+        let marked = cell_span(&game, 0, 0, cursor, peek, &Theme::CLASSIC);
+        assert_eq!(marked.content, Theme::CLASSIC.bomb);
+        assert!(marked.style.add_modifier.contains(Modifier::DIM));
+        let safe = cell_span(&game, 1, 0, cursor, peek, &Theme::CLASSIC);
+        assert_eq!(safe.content, Theme::CLASSIC.empty);
+
+        let hidden = cell_span(&game, 0, 0, cursor, ViewOptions::default(), &Theme::CLASSIC);
+        assert_eq!(hidden.content, Theme::CLASSIC.empty);
+    }
+
+    #[test]
+    fn test_rulers() {
+        assert_eq!(row_ruler_width(9), 2);
+        assert_eq!(row_ruler_width(10), 3);
+        assert_eq!(row_ruler_width(16), 3);
+        assert_eq!(column_ruler_height(1), 1);
+        assert_eq!(column_ruler_height(26), 1);
+        assert_eq!(column_ruler_height(27), 2);
+
+        // The column ruler lines up with the board rows and reads like go-to input
+        let game = SweeperGame::new(30, 16, 0);
+        let (dx, dy) = (row_ruler_width(16), column_ruler_height(30));
+        let ruler = column_ruler(game.get_width(), game.get_height());
+        assert_eq!(ruler.len(), dy);
+        assert!(ruler
+            .iter()
+            .all(|line| line.width() == dx + game.get_width() * 2));
+        assert_eq!(
+            ruler[0].to_string().trim_end(),
+            format!("{}a a a a", " ".repeat(3 + 26 * 2 + 1))
+        );
+        assert!(ruler[1].to_string().ends_with(" y z a b c d"));
+    }
+
+    #[test]
+    fn test_fog_uniform_background() {
+        let mut game = SweeperGame::new(3, 3, 0);
+
+        // Layout
+        // . 1 x
+        // . 2 #
+        // . 1 F
+        game.place_bombs(&[(2, 0), (2, 2)]).unwrap();
+        game.start();
+        game.open(0, 0);
+        game.flag(2, 2);
+
+        let fog = ViewOptions {
+            fog: true,
+            assist: true,
+            ..Default::default()
+        };
+        let cursor = (1, 1);
+        for y in 0..3 {
+            for x in 0..3 {
+                let bg = cell_span(&game, x, y, cursor, fog, &Theme::CLASSIC)
+                    .style
+                    .bg;
+                if (x, y) == cursor {
+                    assert_eq!(bg, Some(Color::Black));
+                } else {
+                    assert_eq!(bg, Some(Color::DarkGray), "({x}, {y})");
+                }
+            }
+        }
+
+        let hidden = cell_span(&game, 2, 1, cursor, ViewOptions::default(), &Theme::CLASSIC);
+        assert_ne!(hidden.style.bg, Some(Color::DarkGray));
+    }
+
+    #[test]
+    fn test_draw_zero_bomb_win() {
+        use ratatui::{backend::TestBackend, widgets::Paragraph, Terminal};
+
+        let mut game = SweeperGame::new(4, 3, 0);
+        game.start();
+        game.open(0, 0);
+
+        let view = draw_game(&game, (0, 0), ViewOptions::default(), &Theme::CLASSIC, 0);
+        let paragraph = Paragraph::new(view.into_text());
+        let mut terminal = Terminal::new(TestBackend::new(20, 10)).unwrap();
+        terminal
+            .draw(|frame| frame.render_widget(paragraph, frame.area()))
+            .unwrap();
+        let content: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(content.contains("You Win!"));
+        assert!(content.contains("Mines: 0"));
+        assert!(content.contains("Cleared: 100%"));
+        assert!(content.contains(&BAR_FULL.to_string().repeat(8)));
+    }
+
+    #[test]
+    fn test_draw_running_header() {
+        let mut game = SweeperGame::new(4, 2, 0);
+        game.flag_budget = Some(3);
+
+        // Layout
+        // x 1 . .
+        // 1 1 . .
+        game.place_bombs(&[(0, 0)]).unwrap();
+        game.start();
+        game.open(1, 0);
+        game.flag(3, 1);
+
+        let assist = ViewOptions {
+            assist: true,
+            show_rulers: true,
+            ..Default::default()
+        };
+        let view = draw_game(&game, (0, 0), assist, &Theme::NERD, 0);
+        let header: Vec<String> = view.header.iter().map(|line| line.to_string()).collect();
+        assert_eq!(
+            header,
+            [
+                "Time: 0",
+                "Mines: 1",
+                "Cleared: 14%",
+                "Remaining: 0",
+                "Flags left: 2",
+                "Safe cells left: 6 Unflagged mines: 1 Undecidable: 5",
+                &BAR_FULL.to_string().repeat(8),
+                "   a b c d",
+            ]
+        );
+
+        assert_eq!(view.row_labels.len(), 2);
+        assert_eq!(view.cells.len(), 2);
+        assert_eq!(view.cells[0][1].content, "1 ");
+        assert_eq!(view.cells[1][3].content, Theme::NERD.flag);
+
+        let text = view.into_text();
+        assert_eq!(text.lines.len(), 10);
+        assert_eq!(text.lines[8].spans.len(), 5);
+    }
+
+    #[test]
+    fn test_cleared_rounds_down() {
+        let mut game = SweeperGame::new(30, 16, 0);
+
+        // Layout
+        // F . … . 1 x
+        // . . … . 1 1
+        // . . … . . .
+        game.place_bombs(&[(29, 0)]).unwrap();
+        game.start();
+        game.flag(0, 0);
+        game.open(0, 15);
+        assert_eq!(game.num_revealed, 478);
+        assert_eq!(game.state, GameState::Running);
+
+        let view = draw_game(&game, (0, 0), ViewOptions::default(), &Theme::CLASSIC, 0);
+        assert_eq!(view.header[2].to_string(), "Cleared: 99%");
+    }
+
+    #[test]
+    fn test_perfect_flags_star() {
+        let result_line = |flag_bomb: bool| {
+            let mut game = SweeperGame::new(3, 1, 0);
+            game.auto_flag_on_win = true;
+
+            // Layout
+            // x 1 .
+            game.place_bombs(&[(0, 0)]).unwrap();
+            game.start();
+            if flag_bomb {
+                game.flag(0, 0);
+            }
+            game.open(2, 0);
+            let view = draw_game(&game, (0, 0), ViewOptions::default(), &Theme::CLASSIC, 0);
+            view.header[3].to_string()
+        };
+        assert_eq!(result_line(true), "You Win! ⭐");
+        assert_eq!(result_line(false), "You Win!");
+    }
+
+    #[test]
+    fn test_animation_frame() {
+        assert_eq!(animation_frame(0), 0);
+        assert_eq!(animation_frame(TICKS_PER_FRAME - 1), 0);
+        assert_eq!(animation_frame(TICKS_PER_FRAME), 1);
+        assert_eq!(animation_frame(3 * TICKS_PER_FRAME + 1), 3);
+        assert_eq!(
+            animation_frame(ANIMATION_FRAMES * TICKS_PER_FRAME),
+            ANIMATION_FRAMES - 1
+        );
+        assert_eq!(animation_frame(usize::MAX), ANIMATION_FRAMES - 1);
+
+        let theme = Theme::CLASSIC;
+        let win = game_over_line(GameState::Win, TICKS_PER_FRAME * 2, &theme).unwrap();
+        assert_eq!(win.to_string(), theme.flag.repeat(3));
+        let lose = game_over_line(GameState::Lose, 0, &theme).unwrap();
+        assert_eq!(lose.to_string(), EXPLOSION_FRAMES[0]);
+        assert!(game_over_line(GameState::Running, 10, &theme).is_none());
+        assert!(game_over_line(GameState::Abandoned, 10, &theme).is_none());
+    }
+
+    #[test]
+    fn test_detonated_bomb() {
+        let mut game = SweeperGame::new(3, 1, 0);
+        game.place_bombs(&[(0, 0), (2, 0)]).unwrap();
+        game.start();
+        game.open(2, 0);
+
+        let cursor = (2, 0);
+        let detonated = cell_span(&game, 2, 0, cursor, ViewOptions::default(), &Theme::CLASSIC);
+        assert_eq!(detonated.content, Theme::CLASSIC.bomb);
+        assert_eq!(detonated.style.bg, Some(Color::Red));
+        let other = cell_span(&game, 0, 0, cursor, ViewOptions::default(), &Theme::CLASSIC);
+        assert_eq!(other.content, Theme::CLASSIC.bomb);
+        assert_ne!(other.style.bg, Some(Color::Red));
+    }
+
+    #[test]
+    fn test_large_counts() {
+        let mut game = SweeperGame::new(3, 3, 0);
+
+        // Layout
+        // x x x
+        // x 8 x
+        // x x x
+        let ring = [
+            (0, 0),
+            (1, 0),
+            (2, 0),
+            (0, 1),
+            (2, 1),
+            (0, 2),
+            (1, 2),
+            (2, 2),
+        ];
+        game.place_bombs(&ring).unwrap();
+        game.start();
+        game.open(1, 1);
+        let span = cell_span(&game, 1, 1, (0, 0), ViewOptions::default(), &Theme::CLASSIC);
+        assert_eq!(span.content, "８");
+
+        // Counts only reachable with more neighbors, such as on a torus
+        for count in [9, 24] {
+            game.board.cells[4].mine_count = count;
+            let span = cell_span(&game, 1, 1, (0, 0), ViewOptions::default(), &Theme::NERD);
+            assert_eq!(span.content, Theme::NERD.many);
+        }
+    }
+
+    #[test]
+    fn test_safe_hint_marker() {
+        let mut game = SweeperGame::new(3, 3, 0);
+
+        // Layout
+        // x x .
+        // 2 2 1
+        // . . .
+        game.place_bombs(&[(0, 0), (1, 0)]).unwrap();
+        game.start();
+        game.open(0, 2);
+
+        let assist = ViewOptions {
+            assist: true,
+            ..Default::default()
+        };
+        for y in 0..3 {
+            for x in 0..3 {
+                let marked =
+                    cell_span(&game, x, y, (x, y), assist, &Theme::CLASSIC).content == SAFE_HINT;
+                assert_eq!(marked, (x, y) == (2, 0), "({x}, {y})");
             }
-        }))
-    }));
+        }
+
+        // Only under the cursor and only with assist on
+        let hint =
+            |cursor, options| cell_span(&game, 2, 0, cursor, options, &Theme::CLASSIC).content;
+        assert_eq!(hint((0, 0), assist), Theme::CLASSIC.empty);
+        assert_eq!(hint((2, 0), ViewOptions::default()), Theme::CLASSIC.empty);
+    }
+
+    #[test]
+    fn test_safe_hint_needs_deduction() {
+        let mut game = SweeperGame::new(2, 3, 0);
 
-    let mut text = Text::default();
-    text.lines.push(time_line);
-    text.lines.push(mine_count_line);
-    text.lines.push(bomb_count_line);
-    text.lines.extend(board_text.lines);
+        // Layout
+        // x .
+        // 1 1
+        // . .
+        game.place_bombs(&[(0, 0)]).unwrap();
+        game.start();
+        game.open(0, 2);
 
-    Paragraph::new(text)
+        // The top right cell is safe, but a 50/50 for the solver
+        let assist = ViewOptions {
+            assist: true,
+            ..Default::default()
+        };
+        for cursor in [(1, 0), (0, 0)] {
+            let span = cell_span(&game, 1, 0, cursor, assist, &Theme::CLASSIC);
+            assert_eq!(span.content, Theme::CLASSIC.empty);
+            assert_ne!(span.style.bg, Some(Color::LightGreen));
+        }
+    }
 }
@@ -0,0 +1,117 @@
+use ratatui::style::Color;
+
+/// Glyphs and colors used to draw the board. Every glyph is two columns wide.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub name: &'static str,
+    /// Glyphs for revealed cells, indexed by mine count.
+    pub numbers: [&'static str; 9],
+    pub number_colors: [Color; 9],
+    /// Glyph for counts beyond `numbers`, which need more than eight neighbors.
+    pub many: &'static str,
+    pub bomb: &'static str,
+    pub flag: &'static str,
+    pub empty: &'static str,
+}
+
+impl Theme {
+    /// Fullwidth digits and emoji.
+    pub const CLASSIC: Theme = Theme {
+        name: "classic",
+        numbers: ["　", "１", "２", "３", "４", "５", "６", "７", "８"],
+        number_colors: NUMBER_COLORS,
+        many: "＋",
+        bomb: "💣",
+        flag: "🚩",
+        empty: "　",
+    };
+
+    /// ASCII digits and Nerd Font icons, padded to two columns.
+    pub const NERD: Theme = Theme {
+        name: "nerd",
+        numbers: ["  ", "1 ", "2 ", "3 ", "4 ", "5 ", "6 ", "7 ", "8 "],
+        number_colors: NUMBER_COLORS,
+        many: "+ ",
+        bomb: "\u{f0691} ",
+        flag: "\u{f023b} ",
+        empty: "  ",
+    };
+
+    pub const BUILTIN: [Theme; 2] = [Theme::CLASSIC, Theme::NERD];
+
+    /// Glyph for a revealed cell with `count` adjacent mines, or `many` past the table.
+    pub fn num_glyph(&self, count: u8) -> &'static str {
+        self.numbers
+            .get(count as usize)
+            .copied()
+            .unwrap_or(self.many)
+    }
+
+    /// Color for a revealed cell with `count` adjacent mines; the last color is reused past
+    /// the table.
+    pub fn num_color(&self, count: u8) -> Color {
+        let last = self.number_colors.len() - 1;
+        self.number_colors[(count as usize).min(last)]
+    }
+
+    /// Built-in theme with the given name.
+    pub fn by_name(name: &str) -> Option<Theme> {
+        Theme::BUILTIN.into_iter().find(|theme| theme.name == name)
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::CLASSIC
+    }
+}
+
+const NUMBER_COLORS: [Color; 9] = [
+    Color::Black,
+    Color::Blue,
+    Color::Green,
+    Color::Red,
+    Color::Magenta,
+    Color::Cyan,
+    Color::Yellow,
+    Color::White,
+    Color::Gray,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::text::Span;
+
+    #[test]
+    fn test_builtin_themes() {
+        for theme in Theme::BUILTIN {
+            let glyphs =
+                theme
+                    .numbers
+                    .iter()
+                    .chain([&theme.many, &theme.bomb, &theme.flag, &theme.empty]);
+            for glyph in glyphs {
+                assert_eq!(Span::raw(*glyph).width(), 2, "{glyph:?} in {}", theme.name);
+            }
+        }
+
+        assert!(Theme::NERD.numbers.iter().all(|glyph| glyph.is_ascii()));
+        assert!(Theme::NERD.empty.is_ascii());
+        assert!(Theme::NERD.many.is_ascii());
+        assert_eq!(Theme::by_name("nerd"), Some(Theme::NERD));
+        assert_eq!(Theme::by_name("missing"), None);
+    }
+
+    #[test]
+    fn test_num_glyph() {
+        let theme = Theme::CLASSIC;
+        assert_eq!(theme.num_glyph(3), "３");
+        assert_eq!(theme.num_glyph(8), "８");
+        assert_eq!(theme.num_color(8), Color::Gray);
+        for count in [9, 24, u8::MAX] {
+            assert_eq!(theme.num_glyph(count), theme.many);
+            assert_eq!(theme.num_color(count), Color::Gray);
+        }
+    }
+}
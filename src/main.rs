@@ -1,12 +1,37 @@
 use app::App;
+use color_eyre::eyre::{eyre, WrapErr};
+use model::sim::SimSession;
+use model::sweeper::SweeperGame;
 
 pub mod app;
 pub mod model;
 
 fn main() -> color_eyre::Result<()> {
     color_eyre::install()?;
+    let mut args = std::env::args().skip(1);
+    if args.next().as_deref() == Some("--script") {
+        let path = args.next().ok_or_else(|| eyre!("--script needs a file"))?;
+        return run_script(&path);
+    }
+
     let terminal = ratatui::init();
-    let result = App::new().run(terminal);
+    // Anything that fails here must still reach `restore`
+    let result = terminal
+        .size()
+        .map_err(Into::into)
+        .and_then(|size| App::new_with_terminal_size(size).run(terminal));
     ratatui::restore();
     result
 }
+
+/// Play the commands of a script file without a terminal UI and print the final board.
+/// The game is a beginner board unless the script starts a new one.
+fn run_script(path: &str) -> color_eyre::Result<()> {
+    let script = std::fs::read_to_string(path).wrap_err_with(|| format!("reading {path}"))?;
+    let mut session = SimSession::new(SweeperGame::new(10, 10, 10));
+    session
+        .run_script(&script)
+        .wrap_err_with(|| format!("running {path}"))?;
+    print!("{}", session.game());
+    Ok(())
+}
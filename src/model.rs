@@ -1 +1,4 @@
+#[cfg(test)]
+mod bench;
+pub mod sim;
 pub mod sweeper;
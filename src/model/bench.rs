@@ -0,0 +1,84 @@
+//! Operations measured by the timing harness, built only on the public game API.
+
+use super::sweeper::{SolveProgress, SweeperGame};
+
+/// Expert board dimensions and bomb count, as in the app's preset.
+pub const EXPERT: (usize, usize, usize) = (30, 16, 99);
+
+/// Generate an empty `size`×`size` board and clear it with one open from the center.
+/// Returns the number of revealed cells.
+pub fn generate_and_clear(size: usize) -> usize {
+    let mut game = SweeperGame::new(size, size, 0);
+    let center = (size / 2) as isize;
+    game.generate_board(center, center)
+        .expect("center of the board is a valid first click");
+    game.start();
+    game.open(center, center);
+    game.num_revealed
+}
+
+/// Generate the expert board for `seed`, open its center and solve as far as possible.
+pub fn solve_seeded_expert(seed: u64) -> SolveProgress {
+    let (width, height, num_bombs) = EXPERT;
+    let mut game = SweeperGame::new(width, height, num_bombs);
+    game.seed = Some(seed);
+    let (x, y) = ((width / 2) as isize, (height / 2) as isize);
+    game.generate_board(x, y)
+        .expect("center of the board is a valid first click");
+    game.start();
+    game.open(x, y);
+    game.solve_all()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    /// Average time of `iterations` runs of `f`.
+    fn time<T>(iterations: u32, mut f: impl FnMut() -> T) -> Duration {
+        let start = Instant::now();
+        for _ in 0..iterations {
+            std::hint::black_box(f());
+        }
+        start.elapsed() / iterations
+    }
+
+    #[test]
+    fn test_operations() {
+        for size in [1, 10, 50] {
+            assert_eq!(generate_and_clear(size), size * size);
+        }
+        assert!(!solve_seeded_expert(0).is_stuck());
+    }
+
+    // Budgets are generous enough for unoptimized builds on slow machines
+
+    #[test]
+    #[ignore = "benchmark, run with --ignored --nocapture"]
+    fn bench_generate_and_clear() {
+        for (size, budget) in [(10, 5), (50, 50), (200, 2000)] {
+            let per_iter = time(5, || generate_and_clear(size));
+            println!("generate_and_clear({size}): {per_iter:?}/iter");
+            assert!(
+                per_iter < Duration::from_millis(budget),
+                "generate_and_clear({size}) took {per_iter:?}"
+            );
+        }
+    }
+
+    #[test]
+    #[ignore = "benchmark, run with --ignored --nocapture"]
+    fn bench_solve_seeded_expert() {
+        let per_iter = time(20, || {
+            (0..10)
+                .map(|seed| solve_seeded_expert(seed).revealed)
+                .sum::<usize>()
+        });
+        println!("solve_seeded_expert x10: {per_iter:?}/iter");
+        assert!(
+            per_iter < Duration::from_millis(500),
+            "solving 10 boards took {per_iter:?}"
+        );
+    }
+}
@@ -0,0 +1,297 @@
+//! Headless play driven by text commands, for bots and tests.
+
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use super::sweeper::{GameState, NewGameError, SweeperGame};
+
+/// Error from a single command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandError {
+    UnknownCommand(String),
+    /// The command expects a different number of arguments.
+    WrongArgumentCount {
+        expected: RangeInclusive<usize>,
+        found: usize,
+    },
+    InvalidNumber(String),
+    OutsideBoard(isize, isize),
+    /// A move was made after the game ended in the given state.
+    GameOver(GameState),
+    InvalidGame(NewGameError),
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CommandError::UnknownCommand(command) => write!(f, "unknown command `{command}`"),
+            CommandError::WrongArgumentCount { expected, found } => {
+                let (min, max) = (expected.start(), expected.end());
+                if min == max {
+                    write!(f, "expected {min} arguments, found {found}")
+                } else {
+                    write!(f, "expected {min} or {max} arguments, found {found}")
+                }
+            }
+            CommandError::InvalidNumber(value) => write!(f, "`{value}` is not a valid number"),
+            CommandError::OutsideBoard(x, y) => write!(f, "({x}, {y}) is outside the board"),
+            CommandError::InvalidGame(error) => write!(f, "{error}"),
+            CommandError::GameOver(state) => write!(f, "the game is over ({state:?})"),
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+/// Error from a script, with the 1-based line of the failing command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptError {
+    pub line: usize,
+    pub error: CommandError,
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.error)
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+/// A game played through commands, one per line:
+///
+/// - `open x y`, `flag x y` and `chord x y` act on a cell. The first `open` generates the
+///   board around it and starts the game. Once the game is won or lost they are rejected.
+/// - `new width height bombs [seed]` replaces the game with a fresh one.
+///
+/// Blank lines and lines starting with `#` are skipped.
+#[derive(Debug)]
+pub struct SimSession {
+    game: SweeperGame,
+}
+
+impl SimSession {
+    pub fn new(game: SweeperGame) -> Self {
+        Self { game }
+    }
+
+    pub fn game(&self) -> &SweeperGame {
+        &self.game
+    }
+
+    /// Run every command of `script`, stopping at the first error.
+    /// Returns the state after the last command.
+    pub fn run_script(&mut self, script: &str) -> Result<GameState, ScriptError> {
+        for (i, line) in script.lines().enumerate() {
+            self.run_command(line)
+                .map_err(|error| ScriptError { line: i + 1, error })?;
+        }
+        Ok(self.game.state)
+    }
+
+    /// Run a single command and return the resulting state.
+    pub fn run_command(&mut self, command: &str) -> Result<GameState, CommandError> {
+        let mut words = command.split_whitespace();
+        let Some(name) = words.next().filter(|name| !name.starts_with('#')) else {
+            return Ok(self.game.state);
+        };
+        if !matches!(name, "new" | "open" | "flag" | "chord") {
+            return Err(CommandError::UnknownCommand(name.to_string()));
+        }
+        let args = words.map(parse_number).collect::<Result<Vec<_>, _>>()?;
+
+        if name == "new" {
+            let (width, height, bombs, seed) = match *args.as_slice() {
+                [width, height, bombs] => (width, height, bombs, None),
+                [width, height, bombs, seed] => (width, height, bombs, Some(seed)),
+                _ => return Err(argument_count(3..=4, args.len())),
+            };
+            let mut game = SweeperGame::try_new(convert(width)?, convert(height)?, convert(bombs)?)
+                .map_err(CommandError::InvalidGame)?;
+            game.seed = seed.map(convert).transpose()?;
+            self.game = game;
+            return Ok(self.game.state);
+        }
+
+        let [x, y] = *args.as_slice() else {
+            return Err(argument_count(2..=2, args.len()));
+        };
+        if self.game.state.is_terminal() {
+            return Err(CommandError::GameOver(self.game.state));
+        }
+        let (x, y) = (x as isize, y as isize);
+        if !self.game.is_valid_coordinate(x, y) {
+            return Err(CommandError::OutsideBoard(x, y));
+        }
+        match name {
+            "open" => {
+                if self.game.state == GameState::NotRunning {
+                    self.game
+                        .generate_board(x, y)
+                        .expect("coordinate is on the board");
+                    self.game.start();
+                }
+                self.game.open(x, y);
+            }
+            "flag" => {
+                self.game.flag(x, y);
+            }
+            _ => {
+                let mut result = self.game.chord(x, y);
+                while let Some(token) = result.pending.take() {
+                    result = self.game.resume_reveal(token);
+                }
+            }
+        }
+        Ok(self.game.state)
+    }
+}
+
+fn parse_number(word: &str) -> Result<i64, CommandError> {
+    word.parse()
+        .map_err(|_| CommandError::InvalidNumber(word.to_string()))
+}
+
+/// Convert a parsed number to a size or seed, rejecting negative values.
+fn convert<T: TryFrom<i64>>(value: i64) -> Result<T, CommandError> {
+    T::try_from(value).map_err(|_| CommandError::InvalidNumber(value.to_string()))
+}
+
+fn argument_count(expected: RangeInclusive<usize>, found: usize) -> CommandError {
+    CommandError::WrongArgumentCount { expected, found }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::sweeper::Cell;
+
+    /// The seeded 5x5 board with 4 bombs, generated and opened at `(0, 0)`.
+    fn seeded_game(seed: u64) -> SweeperGame {
+        let mut game = SweeperGame::new(5, 5, 4);
+        game.seed = Some(seed);
+        game.generate_board(0, 0).unwrap();
+        game.start();
+        game.open(0, 0);
+        game
+    }
+
+    /// Coordinates of the cells of `game` matching `filter`.
+    fn cells(game: &SweeperGame, filter: impl Fn(&Cell) -> bool) -> Vec<(usize, usize)> {
+        (0..game.board.cells.len())
+            .filter(|&i| filter(&game.board.cells[i]))
+            .map(|i| (i % 5, i / 5))
+            .collect()
+    }
+
+    #[test]
+    fn test_script_win() {
+        let mut game = seeded_game(7);
+        let mut script = String::from("# seeded 5x5\nnew 5 5 4 7\n\nopen 0 0\n");
+        for (x, y) in cells(&game, |cell| cell.is_bomb) {
+            script.push_str(&format!("flag {x} {y}\n"));
+        }
+        // Only the cells no earlier cascade revealed, as moves after the win are rejected
+        for (x, y) in cells(&game, |cell| !cell.is_bomb) {
+            if !game.board.cells[y * 5 + x].is_revealed {
+                game.open(x as isize, y as isize);
+                script.push_str(&format!("open {x} {y}\n"));
+            }
+        }
+        assert_eq!(game.state, GameState::Win);
+
+        let mut session = SimSession::new(SweeperGame::new(1, 1, 0));
+        assert_eq!(session.run_script(&script), Ok(GameState::Win));
+        assert_eq!(session.game().num_flags, 4);
+    }
+
+    #[test]
+    fn test_script_lose() {
+        let (x, y) = cells(&seeded_game(7), |cell| cell.is_bomb)[0];
+        let script = format!("new 5 5 4 7\nopen 0 0\nopen {x} {y}\n");
+
+        let mut session = SimSession::new(SweeperGame::new(1, 1, 0));
+        assert_eq!(session.run_script(&script), Ok(GameState::Lose));
+        assert_eq!(session.game().last_opened, Some(y * 5 + x));
+
+        // The finished game takes no more moves
+        let num_revealed = session.game().num_revealed;
+        for command in ["open 4 4", "flag 4 4", "chord 0 0"] {
+            assert_eq!(
+                session.run_command(command),
+                Err(CommandError::GameOver(GameState::Lose))
+            );
+        }
+        assert_eq!(session.game().num_revealed, num_revealed);
+        assert_eq!(session.game().num_flags, 0);
+
+        // Until a new one starts
+        assert_eq!(session.run_command("new 5 5 4"), Ok(GameState::NotRunning));
+    }
+
+    #[test]
+    fn test_script_errors() {
+        let mut session = SimSession::new(SweeperGame::new(3, 3, 1));
+        let error = |script: &str| SimSession::new(SweeperGame::new(3, 3, 1)).run_script(script);
+
+        assert_eq!(
+            error("open 0 0\n\ndig 1 1"),
+            Err(ScriptError {
+                line: 3,
+                error: CommandError::UnknownCommand("dig".to_string())
+            })
+        );
+        assert_eq!(
+            error("flag 1"),
+            Err(ScriptError {
+                line: 1,
+                error: CommandError::WrongArgumentCount {
+                    expected: 2..=2,
+                    found: 1
+                }
+            })
+        );
+        assert_eq!(
+            error("# comment\nnew 3 x 1"),
+            Err(ScriptError {
+                line: 2,
+                error: CommandError::InvalidNumber("x".to_string())
+            })
+        );
+        assert_eq!(
+            error("new 3 3 -1"),
+            Err(ScriptError {
+                line: 1,
+                error: CommandError::InvalidNumber("-1".to_string())
+            })
+        );
+        assert_eq!(
+            error("new 2 2 4"),
+            Err(ScriptError {
+                line: 1,
+                error: CommandError::InvalidGame(NewGameError::TooManyBombs { bombs: 4, cells: 4 })
+            })
+        );
+        let arity = error("new 3 3").unwrap_err();
+        assert_eq!(
+            arity.to_string(),
+            "line 1: expected 3 or 4 arguments, found 2"
+        );
+        let outside = error("open 3 0").unwrap_err();
+        assert_eq!(outside.error, CommandError::OutsideBoard(3, 0));
+        assert_eq!(outside.to_string(), "line 1: (3, 0) is outside the board");
+
+        // Commands before the error still ran
+        assert_eq!(
+            session.run_script("flag 1 1\nchord"),
+            Err(ScriptError {
+                line: 2,
+                error: CommandError::WrongArgumentCount {
+                    expected: 2..=2,
+                    found: 0
+                }
+            })
+        );
+        assert_eq!(session.game().num_flags, 1);
+    }
+}
@@ -1,9 +1,15 @@
-use rand::seq::IteratorRandom;
+use rand::{seq::IteratorRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 use std::{
     collections::VecDeque,
+    fmt,
     time::{Duration, Instant},
 };
 
+mod solver;
+
+pub use solver::SolveProgress;
+
 #[derive(Debug, Clone, Copy, Default)]
 pub struct Cell {
     pub is_bomb: bool,
@@ -12,6 +18,43 @@ pub struct Cell {
     pub mine_count: u8,
 }
 
+/// What the player should see for a cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellDisplay {
+    Hidden,
+    Flagged,
+    Revealed(u8),
+    Bomb,
+}
+
+impl CellDisplay {
+    /// Single ASCII character used for plain-text rendering.
+    pub fn ascii(&self) -> char {
+        match self {
+            CellDisplay::Hidden => '#',
+            CellDisplay::Flagged => 'F',
+            CellDisplay::Revealed(0) => '.',
+            CellDisplay::Revealed(count) => char::from_digit(*count as u32, 10).unwrap_or('?'),
+            CellDisplay::Bomb => '*',
+        }
+    }
+}
+
+impl Cell {
+    /// Decide how the cell is displayed, showing bombs if the game is over.
+    pub fn display_kind(&self, game_over: bool) -> CellDisplay {
+        if game_over && self.is_bomb {
+            CellDisplay::Bomb
+        } else if self.is_revealed {
+            CellDisplay::Revealed(self.mine_count)
+        } else if self.is_flagged {
+            CellDisplay::Flagged
+        } else {
+            CellDisplay::Hidden
+        }
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct Board {
     pub width: usize,
@@ -19,15 +62,196 @@ pub struct Board {
     pub cells: Vec<Cell>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+impl Board {
+    pub fn in_bounds(&self, x: isize, y: isize) -> bool {
+        x >= 0 && x < self.width as isize && y >= 0 && y < self.height as isize
+    }
+
+    /// Index into `cells` of the given coordinate, if it is on the board.
+    pub fn index(&self, x: isize, y: isize) -> Option<usize> {
+        if self.in_bounds(x, y) {
+            Some(y as usize * self.width + x as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Fraction of cells that are bombs.
+    pub fn density(&self) -> f64 {
+        if self.cells.is_empty() {
+            return 0.0;
+        }
+        let num_bombs = self.cells.iter().filter(|cell| cell.is_bomb).count();
+        num_bombs as f64 / self.cells.len() as f64
+    }
+}
+
+/// Bomb count giving roughly the target density on a board, leaving room for a safe first click.
+pub fn bomb_count_for_density(width: usize, height: usize, density: f64) -> usize {
+    let cells = width * height;
+    let bomb_count = (density.clamp(0.0, 1.0) * cells as f64).round() as usize;
+    bomb_count.min(cells.saturating_sub(1))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GameState {
     NotRunning,
     Running,
     Win,
     Lose,
+    /// Given up without revealing the bomb layout.
+    Abandoned,
+}
+
+impl GameState {
+    /// The game is over and no longer accepts moves.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            GameState::Win | GameState::Lose | GameState::Abandoned
+        )
+    }
+
+    /// The game still accepts moves, whether or not the clock has started.
+    pub fn is_active(&self) -> bool {
+        !self.is_terminal()
+    }
+}
+
+/// Remaining cascade of a capped reveal, resumed with [`SweeperGame::resume_reveal`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct RevealToken {
+    queue: VecDeque<usize>,
+}
+
+/// Error returned when a board can't be generated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GenerateError {
+    /// The first click, or a bomb to place, is outside the board.
+    InvalidCoordinate(isize, isize),
+    /// Bombs can only be placed before the game starts.
+    AlreadyStarted,
+}
+
+impl fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GenerateError::InvalidCoordinate(x, y) => {
+                write!(f, "({x}, {y}) is outside the board")
+            }
+            GenerateError::AlreadyStarted => write!(f, "the game has already started"),
+        }
+    }
+}
+
+impl std::error::Error for GenerateError {}
+
+/// Default limit on the number of cells of a new game.
+pub const MAX_CELLS: usize = 1 << 24;
+
+/// Error returned when a game can't be created with the requested size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NewGameError {
+    /// `width * height` doesn't fit in a `usize`.
+    Overflow,
+    TooManyCells {
+        cells: usize,
+        max_cells: usize,
+    },
+    /// At least one cell has to be free of bombs.
+    TooManyBombs {
+        bombs: usize,
+        cells: usize,
+    },
+}
+
+impl fmt::Display for NewGameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NewGameError::Overflow => write!(f, "board size overflows"),
+            NewGameError::TooManyCells { cells, max_cells } => {
+                write!(f, "{cells} cells exceed the limit of {max_cells}")
+            }
+            NewGameError::TooManyBombs { bombs, cells } => {
+                write!(
+                    f,
+                    "{bombs} bombs don't fit on {cells} cells with one left free"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for NewGameError {}
+
+/// Error returned when decoding a board from bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The data ends before the header or cells are complete.
+    TooShort,
+    BadMagic,
+    UnsupportedVersion(u8),
+    InvalidState(u8),
+    /// The header counters disagree with the cells.
+    Inconsistent,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::TooShort => write!(f, "data is too short"),
+            DecodeError::BadMagic => write!(f, "not a saved board"),
+            DecodeError::UnsupportedVersion(version) => {
+                write!(f, "unsupported format version {version}")
+            }
+            DecodeError::InvalidState(state) => write!(f, "invalid game state {state}"),
+            DecodeError::Inconsistent => write!(f, "counters don't match the cells"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Outcome of toggling a flag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlagResult {
+    Flagged,
+    Unflagged,
+    /// Nothing changed, e.g. the cell is revealed or out of bounds.
+    Ignored,
+}
+
+/// How much of the board a plain-text rendering exposes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderStyle {
+    /// Only what the player can see.
+    #[default]
+    Visible,
+    /// Every bomb, as if the game were lost.
+    Revealed,
+}
+
+/// Outcome of opening a cell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenResult {
+    pub state: GameState,
+    /// Number of cells revealed by this call.
+    pub revealed: usize,
+    /// Set if the cascade was cut short by `max_reveal_per_call`.
+    pub pending: Option<RevealToken>,
+}
+
+impl OpenResult {
+    /// Whether the call had no effect on the board.
+    pub fn is_noop(&self) -> bool {
+        self.revealed == 0
+    }
 }
 
-#[derive(Debug)]
+/// Callback receiving the old and new state.
+pub type StateChangeHook = Box<dyn FnMut(GameState, GameState)>;
+
 pub struct SweeperGame {
     pub board: Board,
     pub num_bombs: usize,
@@ -36,11 +260,64 @@ pub struct SweeperGame {
     pub state: GameState,
     pub start_time: Option<Instant>,
     pub total_time: Duration,
+    /// Flag every remaining bomb when the game is won.
+    pub auto_flag_on_win: bool,
+    /// Maximum number of cells revealed by a single open before the cascade is paused.
+    pub max_reveal_per_call: Option<usize>,
+    /// Seed for bomb placement; random if `None`. Seeded layouts use ChaCha8, which gives the
+    /// same board on every platform.
+    pub seed: Option<u64>,
+    /// Maximum number of flags placed at once.
+    pub flag_budget: Option<usize>,
+    /// Called whenever `state` changes to a different value.
+    pub on_state_change: Option<StateChangeHook>,
+    /// Index of the bomb whose opening lost the game.
+    pub last_opened: Option<usize>,
+    /// Cells revealed so far by [`Self::open_ordered`], in order.
+    reveal_order: Option<Vec<usize>>,
+    /// Whether exactly the bombs were flagged when the game was won, before auto flagging.
+    won_with_perfect_flags: bool,
 }
 
 impl SweeperGame {
     /// Initialize and start a new game.
+    ///
+    /// # Panics
+    ///
+    /// If [`Self::try_new`] fails.
     pub fn new(width: usize, height: usize, num_bombs: usize) -> Self {
+        Self::try_new(width, height, num_bombs).unwrap()
+    }
+
+    /// Initialize a new game of at most [`MAX_CELLS`] cells.
+    pub fn try_new(width: usize, height: usize, num_bombs: usize) -> Result<Self, NewGameError> {
+        Self::try_new_with_max_cells(width, height, num_bombs, MAX_CELLS)
+    }
+
+    /// Initialize a new game of at most `max_cells` cells, with at least one cell free of bombs.
+    pub fn try_new_with_max_cells(
+        width: usize,
+        height: usize,
+        num_bombs: usize,
+        max_cells: usize,
+    ) -> Result<Self, NewGameError> {
+        let len = width.checked_mul(height).ok_or(NewGameError::Overflow)?;
+        if len > max_cells {
+            return Err(NewGameError::TooManyCells {
+                cells: len,
+                max_cells,
+            });
+        }
+        if num_bombs >= len {
+            return Err(NewGameError::TooManyBombs {
+                bombs: num_bombs,
+                cells: len,
+            });
+        }
+        Ok(Self::with_cells(width, height, num_bombs))
+    }
+
+    fn with_cells(width: usize, height: usize, num_bombs: usize) -> Self {
         let cells = vec![Cell::default(); width * height];
 
         let board = Board {
@@ -57,57 +334,233 @@ impl SweeperGame {
             state: GameState::NotRunning,
             start_time: None,
             total_time: Duration::ZERO,
+            auto_flag_on_win: false,
+            max_reveal_per_call: None,
+            seed: None,
+            flag_budget: None,
+            on_state_change: None,
+            last_opened: None,
+            reveal_order: None,
+            won_with_perfect_flags: false,
+        }
+    }
+
+    /// Initialize a game whose bomb layout is fixed by the given `(year, month, day)`.
+    pub fn daily(width: usize, height: usize, num_bombs: usize, date: (u16, u8, u8)) -> Self {
+        let mut game = Self::new(width, height, num_bombs);
+        game.seed = Some(daily_seed(date));
+        game
+    }
+
+    /// Independent copy for trying out moves. The timers are cleared, an active game becomes
+    /// `Running` and options that would interfere (the state hook, reveal cap, flag budget and
+    /// auto flagging) are dropped.
+    pub fn clone_for_analysis(&self) -> SweeperGame {
+        let mut game = Self::with_cells(self.board.width, self.board.height, self.num_bombs);
+        game.board.cells.clone_from(&self.board.cells);
+        game.num_revealed = self.num_revealed;
+        game.num_flags = self.num_flags;
+        game.state = if self.state.is_active() {
+            GameState::Running
+        } else {
+            self.state
+        };
+        game.seed = self.seed;
+        game.last_opened = self.last_opened;
+        game.won_with_perfect_flags = self.won_with_perfect_flags;
+        game
+    }
+
+    /// Return to a fresh `NotRunning` game with the same dimensions, bomb count and options,
+    /// discarding the bomb layout.
+    pub fn reset(&mut self) {
+        self.board.cells.fill(Cell::default());
+        self.num_revealed = 0;
+        self.num_flags = 0;
+        self.set_state(GameState::NotRunning);
+        self.start_time = None;
+        self.total_time = Duration::ZERO;
+        self.last_opened = None;
+        self.won_with_perfect_flags = false;
+    }
+
+    /// Generate board with bombs, keeping the given cell and, when possible, its neighbors free.
+    pub fn generate_board(&mut self, x: isize, y: isize) -> Result<(), GenerateError> {
+        match self.seed {
+            Some(seed) => self.generate_board_with_rng(x, y, &mut ChaCha8Rng::seed_from_u64(seed)),
+            None => self.generate_board_with_rng(x, y, &mut rand::rng()),
         }
     }
 
-    /// Generate board with bombs, excluding the given cell.
-    pub fn generate_board(&mut self, x: isize, y: isize) {
-        let mut rng = rand::thread_rng();
-        let bomb_indices = (0..self.board.cells.len())
-            .filter(|&i| i != self.cell_index(x, y).unwrap())
-            .choose_multiple(&mut rng, self.num_bombs);
+    /// Generate board with bombs drawn from `rng`, excluding the given cell. Its neighbors only
+    /// get bombs that don't fit anywhere else, so the first click usually opens an area.
+    /// `num_bombs` is lowered to the number placed if the other cells can't hold them all.
+    pub fn generate_board_with_rng<R: Rng + ?Sized>(
+        &mut self,
+        x: isize,
+        y: isize,
+        rng: &mut R,
+    ) -> Result<(), GenerateError> {
+        let first_index = self
+            .cell_index(x, y)
+            .ok_or(GenerateError::InvalidCoordinate(x, y))?;
+        let neighbors = self.adjacent_cells(first_index);
+        let mut bomb_indices = (0..self.board.cells.len())
+            .filter(|&i| i != first_index && !neighbors.contains(&i))
+            .choose_multiple(rng, self.num_bombs);
+        let overflow = self.num_bombs - bomb_indices.len();
+        bomb_indices.extend(neighbors.into_iter().choose_multiple(rng, overflow));
 
+        self.num_bombs = bomb_indices.len();
         for i in bomb_indices {
             self.board.cells[i].is_bomb = true;
         }
+        Ok(())
+    }
+
+    /// Replace the bombs with exactly the given coordinates and set `num_bombs` to match.
+    /// Duplicates count once. Coordinates outside the board are skipped and the first of them
+    /// is reported after the others are placed. Fails without changes unless `NotRunning`.
+    pub fn place_bombs(&mut self, coords: &[(isize, isize)]) -> Result<(), GenerateError> {
+        if self.state != GameState::NotRunning {
+            return Err(GenerateError::AlreadyStarted);
+        }
+        for cell in &mut self.board.cells {
+            cell.is_bomb = false;
+        }
+        let mut result = Ok(());
+        for &(x, y) in coords {
+            match self.cell_index(x, y) {
+                Some(i) => self.board.cells[i].is_bomb = true,
+                None if result.is_ok() => result = Err(GenerateError::InvalidCoordinate(x, y)),
+                None => {}
+            }
+        }
+        self.num_bombs = self.board.cells.iter().filter(|cell| cell.is_bomb).count();
+        result
     }
 
-    /// Unveil the cell at the given coordinate.
+    /// Unveil the cell at the given coordinate, ignoring `max_reveal_per_call`.
     pub fn open(&mut self, x: isize, y: isize) -> GameState {
+        let mut result = self.open_with_result(x, y);
+        while let Some(token) = result.pending.take() {
+            result = self.resume_reveal(token);
+        }
+        result.state
+    }
+
+    /// Like [`Self::open`], also returning the indices of the revealed cells in reveal order.
+    ///
+    /// The order is stable for a given board and click: a chord reveals its targets first,
+    /// then cascades expand breadth first, visiting neighbors in row-major order.
+    pub fn open_ordered(&mut self, x: isize, y: isize) -> (GameState, Vec<usize>) {
+        self.reveal_order = Some(Vec::new());
+        let state = self.open(x, y);
+        (state, self.reveal_order.take().unwrap_or_default())
+    }
+
+    /// Unveil the cell at the given coordinate, reporting how many cells were revealed.
+    /// Flagged cells are left untouched, and nothing happens once the game is over.
+    pub fn open_with_result(&mut self, x: isize, y: isize) -> OpenResult {
+        if self.state.is_terminal() {
+            return self.game_over_result();
+        }
+        let num_revealed = self.num_revealed;
+        let limit = self.reveal_limit();
+        let mut pending = None;
         if let Some(cell_index) = self.cell_index(x, y) {
             let cell = &self.board.cells[cell_index];
             if cell.is_revealed {
                 // Reveal surrounding cells if the number of flags around the cell is equal to the bomb count
-                self.reveal_adjacent_cells(cell_index);
-            } else {
-                self.reveal_cell(cell_index);
+                pending = self.reveal_adjacent_cells(cell_index, limit);
+            } else if !cell.is_flagged {
+                pending = self.reveal_cell(cell_index, limit);
             }
         }
 
-        if self.num_revealed >= self.board.width * self.board.height - self.num_bombs {
-            self.state = GameState::Win;
+        self.finish_reveal(num_revealed, pending)
+    }
+
+    /// Reveal the unflagged neighbors of a revealed number whose flags match its count.
+    /// Does nothing on other cells or once the game is over.
+    pub fn chord(&mut self, x: isize, y: isize) -> OpenResult {
+        if self.state.is_terminal() {
+            return self.game_over_result();
         }
+        let num_revealed = self.num_revealed;
+        let pending = match self.cell_index(x, y) {
+            Some(cell_index) if self.board.cells[cell_index].is_revealed => {
+                self.reveal_adjacent_cells(cell_index, self.reveal_limit())
+            }
+            _ => None,
+        };
 
-        if self.state != GameState::Running {
-            self.end_game();
+        self.finish_reveal(num_revealed, pending)
+    }
+
+    /// Cells a [`Self::chord`] at the given coordinate would open directly, before any cascade,
+    /// or `None` if the cell isn't a revealed number with matching flags.
+    pub fn chord_preview(&self, x: isize, y: isize) -> Option<Vec<usize>> {
+        self.cell_index(x, y)
+            .and_then(|cell_index| self.chord_targets(cell_index))
+    }
+
+    /// Continue a cascade paused by `max_reveal_per_call`.
+    pub fn resume_reveal(&mut self, token: RevealToken) -> OpenResult {
+        if self.state.is_terminal() {
+            return self.game_over_result();
         }
+        let num_revealed = self.num_revealed;
+        let pending = self.reveal_cell_queue(token.queue, self.reveal_limit());
 
-        self.state
+        self.finish_reveal(num_revealed, pending)
     }
 
     /// Toggle flag on the cell at the given coordinate.
-    pub fn flag(&mut self, x: isize, y: isize) {
+    /// New flags are rejected once `flag_budget` is used up.
+    pub fn flag(&mut self, x: isize, y: isize) -> FlagResult {
+        let budget_exhausted = self.flags_left() == Some(0);
+        let mut result = FlagResult::Ignored;
         if let Some(cell_index) = self.cell_index(x, y) {
             let cell = &mut self.board.cells[cell_index];
-            if !cell.is_revealed {
+            if !cell.is_revealed && (cell.is_flagged || !budget_exhausted) {
                 cell.is_flagged = !cell.is_flagged;
                 if cell.is_flagged {
                     self.num_flags += 1;
+                    result = FlagResult::Flagged;
                 } else {
                     self.num_flags -= 1;
+                    result = FlagResult::Unflagged;
                 }
             }
         }
+        debug_assert_eq!(self.num_flags, self.count_flags(), "num_flags drifted");
+        result
+    }
+
+    /// Recompute `num_flags` and `num_revealed` from the cells.
+    pub fn recount(&mut self) {
+        self.num_flags = self.count_flags();
+        self.num_revealed = self
+            .board
+            .cells
+            .iter()
+            .filter(|cell| cell.is_revealed)
+            .count();
+    }
+
+    fn count_flags(&self) -> usize {
+        self.board
+            .cells
+            .iter()
+            .filter(|cell| cell.is_flagged)
+            .count()
+    }
+
+    /// Flags that can still be placed under `flag_budget`.
+    pub fn flags_left(&self) -> Option<usize> {
+        self.flag_budget
+            .map(|budget| budget.saturating_sub(self.num_flags))
     }
 
     pub fn get_width(&self) -> usize {
@@ -119,114 +572,445 @@ impl SweeperGame {
     }
 
     pub fn is_valid_coordinate(&self, x: isize, y: isize) -> bool {
-        x >= 0 && x < self.board.width as isize && y >= 0 && y < self.board.height as isize
+        self.board.in_bounds(x, y)
     }
 
     pub fn get_cell(&self, x: isize, y: isize) -> Option<&Cell> {
         self.cell_index(x, y).map(|index| &self.board.cells[index])
     }
 
+    /// Number of bombs around the given coordinate, regardless of reveal state.
+    pub fn neighbor_mine_count(&self, x: isize, y: isize) -> u8 {
+        self.cell_index(x, y)
+            .map_or(0, |index| self.adjacent_bomb_count(index))
+    }
+
+    /// Number shown on the cell, if it is a revealed non-bomb cell.
+    pub fn revealed_number(&self, x: isize, y: isize) -> Option<u8> {
+        self.get_cell(x, y)
+            .filter(|cell| cell.is_revealed && !cell.is_bomb)
+            .map(|cell| cell.mine_count)
+    }
+
     /// Get an iterator over the board row slices along with their coordinates.
     pub fn cell_row_iter(&self) -> impl Iterator<Item = &[Cell]> {
         self.board.cells.chunks(self.board.width)
     }
 
+    /// Number of safe cells that still have to be revealed.
+    pub fn remaining_safe_cells(&self) -> usize {
+        self.board
+            .cells
+            .iter()
+            .filter(|cell| !cell.is_bomb && !cell.is_revealed)
+            .count()
+    }
+
+    /// Fraction of the safe cells revealed so far, from 0 to 1. A board without safe cells
+    /// counts as fully cleared.
+    pub fn reveal_progress(&self) -> f64 {
+        let safe_cells = self.board.cells.len().saturating_sub(self.num_bombs);
+        if safe_cells == 0 {
+            return 1.0;
+        }
+        (self.num_revealed as f64 / safe_cells as f64).clamp(0.0, 1.0)
+    }
+
+    /// Coordinates of the safe cells that still have to be revealed.
+    /// This exposes the bomb layout, so only use it for assists.
+    pub fn safe_cells_remaining_coords(&self) -> Vec<(isize, isize)> {
+        let width = self.board.width;
+        self.board
+            .cells
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| !cell.is_bomb && !cell.is_revealed)
+            .map(|(i, _)| ((i % width) as isize, (i / width) as isize))
+            .collect()
+    }
+
+    /// Bombs that are not flagged yet, unlike the classic counter that trusts every flag.
+    /// This exposes the bomb layout, so only use it for assists.
+    pub fn true_remaining_bombs(&self) -> usize {
+        self.board
+            .cells
+            .iter()
+            .filter(|cell| cell.is_bomb && !cell.is_flagged)
+            .count()
+    }
+
+    /// Whether exactly the bomb cells are flagged.
+    pub fn flags_match_bombs(&self) -> bool {
+        self.board
+            .cells
+            .iter()
+            .all(|cell| cell.is_flagged == cell.is_bomb)
+    }
+
+    /// Whether the game was won with exactly the bombs flagged by the player, which auto
+    /// flagging on a win doesn't count towards.
+    pub fn won_with_perfect_flags(&self) -> bool {
+        self.won_with_perfect_flags
+    }
+
+    /// Whether the cell is a revealed number with exactly as many flags around it.
+    pub fn is_number_satisfied(&self, x: isize, y: isize) -> bool {
+        match self.cell_index(x, y) {
+            Some(index) => {
+                let cell = &self.board.cells[index];
+                cell.is_revealed
+                    && !cell.is_bomb
+                    && cell.mine_count > 0
+                    && self.adjacent_flag_count(index) == cell.mine_count as usize
+            }
+            None => false,
+        }
+    }
+
+    /// Revealed numbered cells that still have unknown (unrevealed, unflagged) neighbors,
+    /// each paired with those neighbors.
+    pub fn iter_frontier(&self) -> impl Iterator<Item = (usize, Vec<usize>)> + '_ {
+        self.board
+            .cells
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| cell.is_revealed && !cell.is_bomb && cell.mine_count > 0)
+            .filter_map(|(i, _)| {
+                let unknown: Vec<usize> = self
+                    .adjacent_cells(i)
+                    .into_iter()
+                    .filter(|&j| {
+                        let cell = &self.board.cells[j];
+                        !cell.is_revealed && !cell.is_flagged
+                    })
+                    .collect();
+                (!unknown.is_empty()).then_some((i, unknown))
+            })
+    }
+
+    /// Minimum number of clicks needed to clear the board (3BV), from the bomb layout.
+    pub fn three_bv(&self) -> usize {
+        let len = self.board.cells.len();
+        let counts: Vec<u8> = (0..len).map(|i| self.adjacent_bomb_count(i)).collect();
+        let mut marked = vec![false; len];
+        let mut bv = 0;
+
+        // Each zero region, including its numbered border, is cleared by one click
+        for i in 0..len {
+            if marked[i] || self.board.cells[i].is_bomb || counts[i] != 0 {
+                continue;
+            }
+            bv += 1;
+            marked[i] = true;
+            let mut cell_q = VecDeque::from([i]);
+            while let Some(cell_index) = cell_q.pop_front() {
+                for j in self.adjacent_cells(cell_index) {
+                    if !marked[j] {
+                        marked[j] = true;
+                        if counts[j] == 0 {
+                            cell_q.push_back(j);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Remaining numbered cells need a click each
+        bv + (0..len)
+            .filter(|&i| !marked[i] && !self.board.cells[i].is_bomb)
+            .count()
+    }
+
+    /// Render the board as ASCII, one line per row.
+    pub fn to_ascii(&self, style: RenderStyle) -> String {
+        let game_over = self.state == GameState::Lose || style == RenderStyle::Revealed;
+        self.cell_row_iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| cell.display_kind(game_over).ascii())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Render the board as ASCII into `buf`, reusing its allocation.
+    /// Produces the same output as [`Self::to_ascii`].
+    pub fn render_into(&self, buf: &mut String, style: RenderStyle) {
+        let game_over = self.state == GameState::Lose || style == RenderStyle::Revealed;
+        buf.clear();
+        buf.reserve(self.board.cells.len() + self.board.height);
+        for (y, row) in self.cell_row_iter().enumerate() {
+            if y > 0 {
+                buf.push('\n');
+            }
+            buf.extend(row.iter().map(|cell| cell.display_kind(game_over).ascii()));
+        }
+    }
+
     pub fn get_elapsed_time(&self) -> Duration {
         match self.state {
             GameState::Running => match self.start_time {
                 Some(start_time) => start_time.elapsed(),
                 None => Duration::ZERO,
             },
-            GameState::Win | GameState::Lose => self.total_time,
+            GameState::Win | GameState::Lose | GameState::Abandoned => self.total_time,
             _ => Duration::ZERO,
         }
     }
 
+    /// What the player currently sees on each cell, in row-major order.
+    pub fn visible_board(&self) -> Vec<CellDisplay> {
+        let game_over = self.state == GameState::Lose;
+        self.board
+            .cells
+            .iter()
+            .map(|cell| cell.display_kind(game_over))
+            .collect()
+    }
+
+    /// Plain sentence describing the cell at the given coordinate, for screen readers.
+    /// Rows and columns are counted from 1.
+    pub fn describe_cursor(&self, x: isize, y: isize) -> String {
+        let position = format!("Column {}, row {}", x + 1, y + 1);
+        let Some(cell) = self.get_cell(x, y) else {
+            return format!("{position} is outside the board.");
+        };
+        let content = match cell.display_kind(self.state == GameState::Lose) {
+            CellDisplay::Hidden => "hidden".to_string(),
+            CellDisplay::Flagged => "flagged".to_string(),
+            CellDisplay::Revealed(0) => "empty".to_string(),
+            CellDisplay::Revealed(count) => plural(count as usize, "adjacent mine"),
+            CellDisplay::Bomb => "mine".to_string(),
+        };
+        format!("{position}: {content}.")
+    }
+
+    /// Plain sentence with the game state, remaining mines, flags and time, for screen readers.
+    pub fn describe_status(&self) -> String {
+        let state = match self.state {
+            GameState::NotRunning => "Not started",
+            GameState::Running => "In progress",
+            GameState::Win => "Won",
+            GameState::Lose => "Lost",
+            GameState::Abandoned => "Abandoned",
+        };
+        let remaining = self.num_bombs as isize - self.num_flags as isize;
+        format!(
+            "{state}. {remaining} {} remaining, {} placed, {} elapsed.",
+            if remaining.abs() == 1 {
+                "mine"
+            } else {
+                "mines"
+            },
+            plural(self.num_flags, "flag"),
+            plural(self.get_elapsed_time().as_secs() as usize, "second"),
+        )
+    }
+
+    /// End the game as a loss.
+    pub fn resign(&mut self) {
+        if self.state.is_active() {
+            self.set_state(GameState::Lose);
+            self.end_game();
+        }
+    }
+
+    /// End the game without a loss reveal, leaving the board as-is.
+    pub fn abandon(&mut self) {
+        if self.state.is_active() {
+            self.set_state(GameState::Abandoned);
+            self.end_game();
+        }
+    }
+
+    /// Start the game and its timer. Does nothing unless the game is `NotRunning`.
     pub fn start(&mut self) {
-        self.state = GameState::Running;
+        if self.state != GameState::NotRunning {
+            return;
+        }
+        self.set_state(GameState::Running);
         self.start_time = Some(Instant::now());
     }
 
+    /// Assign `state`, notifying `on_state_change` if it actually changed.
+    fn set_state(&mut self, state: GameState) {
+        let old = self.state;
+        self.state = state;
+        if old != state {
+            if let Some(ref mut on_state_change) = self.on_state_change {
+                on_state_change(old, state);
+            }
+        }
+    }
+
     fn end_game(&mut self) {
         if let Some(start_time) = self.start_time {
             self.total_time = start_time.elapsed();
         }
+        self.won_with_perfect_flags = self.state == GameState::Win && self.flags_match_bombs();
+        if self.state == GameState::Win && self.auto_flag_on_win {
+            self.flag_all_bombs();
+        }
     }
 
-    fn reveal_cell(&mut self, cell_index: usize) {
-        self.set_revealed(cell_index);
-        if self.board.cells[cell_index].is_bomb {
-            self.state = GameState::Lose;
-            return;
+    /// Result of a reveal attempted after the game ended. `end_game` already ran when the
+    /// game ended, so it must not run again.
+    fn game_over_result(&self) -> OpenResult {
+        OpenResult {
+            state: self.state,
+            revealed: 0,
+            pending: None,
+        }
+    }
+
+    /// Common exit of every reveal path (direct open, cascade and chord).
+    fn finish_reveal(&mut self, num_revealed: usize, pending: Option<RevealToken>) -> OpenResult {
+        self.check_win();
+
+        if self.state.is_terminal() {
+            self.end_game();
         }
 
-        self.reveal_cell_queue(VecDeque::from([cell_index]));
+        OpenResult {
+            state: self.state,
+            revealed: self.num_revealed - num_revealed,
+            pending: pending.filter(|_| self.state.is_active()),
+        }
     }
 
-    fn reveal_adjacent_cells(&mut self, cell_index: usize) {
-        let adjacent = self.adjacent_cells(cell_index);
-        let flag_count = adjacent
-            .iter()
-            .filter(|&&i| self.board.cells[i].is_flagged)
-            .count();
-        if flag_count != self.board.cells[cell_index].mine_count as usize {
-            return;
+    /// Switch to `Win` once every safe cell is revealed, unless the game already ended.
+    fn check_win(&mut self) {
+        let safe_cells = self.board.cells.len().saturating_sub(self.num_bombs);
+        if self.state.is_active() && self.num_revealed >= safe_cells {
+            self.set_state(GameState::Win);
         }
+    }
 
-        let cell_q = VecDeque::from_iter(
-            adjacent
-                .iter()
-                .filter(|&&i| !self.board.cells[i].is_flagged)
-                .copied(),
-        );
+    /// Value of `num_revealed` at which the current call must pause the cascade.
+    fn reveal_limit(&self) -> Option<usize> {
+        self.max_reveal_per_call
+            .map(|max| self.num_revealed + max.max(1))
+    }
+
+    /// Leave flags on exactly the bombs, dropping wrong flags. The game is over, so the
+    /// `flag_budget` limits play but not this, and `flags_left` simply reaches zero.
+    fn flag_all_bombs(&mut self) {
+        for cell in self.board.cells.iter_mut() {
+            cell.is_flagged = cell.is_bomb;
+        }
+        self.num_flags = self.count_flags();
+    }
+
+    fn reveal_cell(&mut self, cell_index: usize, limit: Option<usize>) -> Option<RevealToken> {
+        self.set_revealed(cell_index);
+        if self.board.cells[cell_index].is_bomb {
+            self.last_opened = Some(cell_index);
+            self.set_state(GameState::Lose);
+            return None;
+        }
+
+        self.reveal_cell_queue(VecDeque::from([cell_index]), limit)
+    }
+
+    fn reveal_adjacent_cells(
+        &mut self,
+        cell_index: usize,
+        limit: Option<usize>,
+    ) -> Option<RevealToken> {
+        let cell_q = VecDeque::from(self.chord_targets(cell_index)?);
         for &i in &cell_q {
             self.set_revealed(i);
             if self.board.cells[i].is_bomb {
-                self.state = GameState::Lose;
-                return;
+                self.last_opened = Some(i);
+                self.set_state(GameState::Lose);
+                return None;
             }
         }
-        self.reveal_cell_queue(cell_q);
+        self.reveal_cell_queue(cell_q, limit)
     }
 
-    fn reveal_cell_queue(&mut self, mut cell_q: VecDeque<usize>) {
+    /// Expand revealed zero cells in the queue, pausing once `num_revealed` reaches `limit`.
+    fn reveal_cell_queue(
+        &mut self,
+        mut cell_q: VecDeque<usize>,
+        limit: Option<usize>,
+    ) -> Option<RevealToken> {
         while let Some(cell_index) = cell_q.pop_front() {
-            let adjacent = self.adjacent_cells(cell_index);
-            let mine_count = adjacent
-                .iter()
-                .filter(|&&i| self.board.cells[i].is_bomb)
-                .count();
-            self.board.cells[cell_index].mine_count = mine_count as u8;
+            if self.board.cells[cell_index].mine_count != 0 {
+                continue;
+            }
 
-            if mine_count == 0 {
-                for j in self.adjacent_cells(cell_index) {
-                    if self.board.cells[j].is_revealed || self.board.cells[j].is_flagged {
-                        continue;
-                    }
+            for j in self.adjacent_cells(cell_index) {
+                if self.board.cells[j].is_revealed || self.board.cells[j].is_flagged {
+                    continue;
+                }
 
-                    self.set_revealed(j);
-                    cell_q.push_back(j);
+                if limit.is_some_and(|limit| self.num_revealed >= limit) {
+                    // Expand this cell again on resume, skipping the neighbors revealed so far
+                    cell_q.push_front(cell_index);
+                    return Some(RevealToken { queue: cell_q });
                 }
+
+                self.set_revealed(j);
+                cell_q.push_back(j);
             }
         }
+        None
     }
 
     fn cell_index(&self, x: isize, y: isize) -> Option<usize> {
-        if self.is_valid_coordinate(x, y) {
-            Some(y as usize * self.board.width + x as usize)
-        } else {
-            None
-        }
+        self.board.index(x, y)
     }
 
     fn set_revealed(&mut self, cell_index: usize) {
         if self.board.cells[cell_index].is_revealed {
             return;
         }
-        self.board.cells[cell_index].is_revealed = true;
+        let mine_count = self.adjacent_bomb_count(cell_index);
+        let cell = &mut self.board.cells[cell_index];
+        cell.is_revealed = true;
+        cell.mine_count = mine_count;
         self.num_revealed += 1;
+        if let Some(order) = self.reveal_order.as_mut() {
+            order.push(cell_index);
+        }
+    }
+
+    /// Cells a chord on `cell_index` would reveal, if it is a revealed number with matching flags.
+    fn chord_targets(&self, cell_index: usize) -> Option<Vec<usize>> {
+        let cell = &self.board.cells[cell_index];
+        if !cell.is_revealed
+            || cell.is_bomb
+            || cell.mine_count == 0
+            || self.adjacent_flag_count(cell_index) != cell.mine_count as usize
+        {
+            return None;
+        }
+
+        Some(
+            self.adjacent_cells(cell_index)
+                .into_iter()
+                .filter(|&i| !self.board.cells[i].is_flagged && !self.board.cells[i].is_revealed)
+                .collect(),
+        )
+    }
+
+    fn adjacent_flag_count(&self, cell_index: usize) -> usize {
+        self.adjacent_cells(cell_index)
+            .iter()
+            .filter(|&&i| self.board.cells[i].is_flagged)
+            .count()
+    }
+
+    fn adjacent_bomb_count(&self, cell_index: usize) -> u8 {
+        self.adjacent_cells(cell_index)
+            .iter()
+            .filter(|&&i| self.board.cells[i].is_bomb)
+            .count() as u8
     }
 
+    /// Neighbors of the cell in row-major order, which keeps cascades deterministic.
     fn adjacent_cells(&self, cell_index: usize) -> Vec<usize> {
         let x = cell_index % self.board.width;
         let y = cell_index / self.board.width;
@@ -238,22 +1022,199 @@ impl SweeperGame {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_new_game() {
-        let mut game = SweeperGame::new(10, 10, 20);
-        game.generate_board(0, 0);
-        assert_eq!(game.state, GameState::NotRunning);
-        game.start();
-        assert_eq!(game.board.width, 10);
-        assert_eq!(game.board.height, 10);
-        assert_eq!(game.num_bombs, 20);
-        assert_eq!(game.num_revealed, 0);
-        assert_eq!(game.num_flags, 0);
-        assert_eq!(game.state, GameState::Running);
+const SAVE_MAGIC: &[u8; 2] = b"RS";
+const SAVE_VERSION: u8 = 1;
+/// Magic, version, five `u32` fields and the state byte.
+const SAVE_HEADER_LEN: usize = 3 + 5 * 4 + 1;
+const SAVE_BITS_PER_CELL: usize = 3;
+
+impl GameState {
+    fn to_byte(self) -> u8 {
+        match self {
+            GameState::NotRunning => 0,
+            GameState::Running => 1,
+            GameState::Win => 2,
+            GameState::Lose => 3,
+            GameState::Abandoned => 4,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(GameState::NotRunning),
+            1 => Some(GameState::Running),
+            2 => Some(GameState::Win),
+            3 => Some(GameState::Lose),
+            4 => Some(GameState::Abandoned),
+            _ => None,
+        }
+    }
+}
+
+impl SweeperGame {
+    /// Encode the board compactly: a small header followed by bomb, flag and revealed bits
+    /// for each cell. Timers and options are not saved.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let cell_bytes = (self.board.cells.len() * SAVE_BITS_PER_CELL).div_ceil(8);
+        let mut bytes = Vec::with_capacity(SAVE_HEADER_LEN + cell_bytes);
+        bytes.extend_from_slice(SAVE_MAGIC);
+        bytes.push(SAVE_VERSION);
+        for value in [
+            self.board.width,
+            self.board.height,
+            self.num_bombs,
+            self.num_revealed,
+            self.num_flags,
+        ] {
+            bytes.extend_from_slice(&(value as u32).to_le_bytes());
+        }
+        bytes.push(self.state.to_byte());
+
+        bytes.resize(SAVE_HEADER_LEN + cell_bytes, 0);
+        for (i, cell) in self.board.cells.iter().enumerate() {
+            for (j, bit) in [cell.is_bomb, cell.is_flagged, cell.is_revealed]
+                .into_iter()
+                .enumerate()
+            {
+                let pos = i * SAVE_BITS_PER_CELL + j;
+                bytes[SAVE_HEADER_LEN + pos / 8] |= (bit as u8) << (pos % 8);
+            }
+        }
+        bytes
+    }
+
+    /// Decode a board produced by [`Self::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let header = bytes.get(..SAVE_HEADER_LEN).ok_or(DecodeError::TooShort)?;
+        if &header[..2] != SAVE_MAGIC {
+            return Err(DecodeError::BadMagic);
+        }
+        if header[2] != SAVE_VERSION {
+            return Err(DecodeError::UnsupportedVersion(header[2]));
+        }
+        let field = |n: usize| {
+            let start = 3 + n * 4;
+            u32::from_le_bytes(header[start..start + 4].try_into().unwrap()) as usize
+        };
+        let (width, height) = (field(0), field(1));
+        let state_byte = header[SAVE_HEADER_LEN - 1];
+        let state =
+            GameState::from_byte(state_byte).ok_or(DecodeError::InvalidState(state_byte))?;
+
+        let len = width.checked_mul(height).ok_or(DecodeError::TooShort)?;
+        let cell_bytes = len
+            .checked_mul(SAVE_BITS_PER_CELL)
+            .ok_or(DecodeError::TooShort)?
+            .div_ceil(8);
+        let data = bytes
+            .get(SAVE_HEADER_LEN..SAVE_HEADER_LEN + cell_bytes)
+            .ok_or(DecodeError::TooShort)?;
+        let bit = |pos: usize| data[pos / 8] & (1 << (pos % 8)) != 0;
+
+        // Rejects a bomb count that leaves no safe cell
+        let mut game =
+            Self::try_new(width, height, field(2)).map_err(|_| DecodeError::Inconsistent)?;
+        for (i, cell) in game.board.cells.iter_mut().enumerate() {
+            let pos = i * SAVE_BITS_PER_CELL;
+            cell.is_bomb = bit(pos);
+            cell.is_flagged = bit(pos + 1);
+            cell.is_revealed = bit(pos + 2);
+        }
+        for i in 0..len {
+            if game.board.cells[i].is_revealed {
+                game.board.cells[i].mine_count = game.adjacent_bomb_count(i);
+            }
+        }
+        game.num_revealed = game.board.cells.iter().filter(|c| c.is_revealed).count();
+        game.num_flags = game.board.cells.iter().filter(|c| c.is_flagged).count();
+        // A board saved before generation has no bombs placed yet
+        let placed = game.board.cells.iter().filter(|c| c.is_bomb).count();
+        if (placed != 0 && placed != game.num_bombs)
+            || game.num_revealed != field(3)
+            || game.num_flags != field(4)
+        {
+            return Err(DecodeError::Inconsistent);
+        }
+        game.state = state;
+        Ok(game)
+    }
+}
+
+impl fmt::Debug for SweeperGame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SweeperGame")
+            .field("board", &self.board)
+            .field("num_bombs", &self.num_bombs)
+            .field("num_revealed", &self.num_revealed)
+            .field("num_flags", &self.num_flags)
+            .field("state", &self.state)
+            .field("start_time", &self.start_time)
+            .field("total_time", &self.total_time)
+            .field("auto_flag_on_win", &self.auto_flag_on_win)
+            .field("max_reveal_per_call", &self.max_reveal_per_call)
+            .field("seed", &self.seed)
+            .field("flag_budget", &self.flag_budget)
+            .field("on_state_change", &self.on_state_change.is_some())
+            .field("last_opened", &self.last_opened)
+            .field("reveal_order", &self.reveal_order)
+            .field("won_with_perfect_flags", &self.won_with_perfect_flags)
+            .finish()
+    }
+}
+
+/// Stable seed for a calendar date (FNV-1a), independent of platform and Rust version.
+pub fn daily_seed((year, month, day): (u16, u8, u8)) -> u64 {
+    let [year_hi, year_lo] = year.to_be_bytes();
+    [year_hi, year_lo, month, day]
+        .iter()
+        .fold(0xcbf29ce484222325, |hash, &byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        })
+}
+
+/// `count` followed by `noun`, with an `s` unless the count is one.
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {noun}")
+    } else {
+        format!("{count} {noun}s")
+    }
+}
+
+impl fmt::Display for SweeperGame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:?} bombs: {} flags: {}",
+            self.state, self.num_bombs, self.num_flags
+        )?;
+        let game_over = self.state == GameState::Lose;
+        for row in self.cell_row_iter() {
+            for cell in row {
+                write!(f, "{}", cell.display_kind(game_over).ascii())?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_game() {
+        let mut game = SweeperGame::new(10, 10, 20);
+        game.generate_board(0, 0).unwrap();
+        assert_eq!(game.state, GameState::NotRunning);
+        game.start();
+        assert_eq!(game.board.width, 10);
+        assert_eq!(game.board.height, 10);
+        assert_eq!(game.num_bombs, 20);
+        assert_eq!(game.num_revealed, 0);
+        assert_eq!(game.num_flags, 0);
+        assert_eq!(game.state, GameState::Running);
 
         // Test sum of bombs
         let num_bombs = game.board.cells.iter().filter(|&cell| cell.is_bomb).count();
@@ -269,6 +1230,25 @@ mod tests {
         assert_eq!(game.cell_index(-1, -1), None);
     }
 
+    #[test]
+    fn test_board_index() {
+        let board = SweeperGame::new(10, 10, 0).board;
+        assert_eq!(board.index(0, 0), Some(0));
+        assert_eq!(board.index(9, 9), Some(99));
+        assert_eq!(board.index(10, 10), None);
+        assert_eq!(board.index(-1, -1), None);
+
+        let board = SweeperGame::new(4, 2, 0).board;
+        assert_eq!(board.index(3, 1), Some(7));
+        assert_eq!(board.index(1, 3), None);
+        assert!(board.in_bounds(3, 0));
+        assert!(!board.in_bounds(4, 0));
+        assert!(!board.in_bounds(0, 2));
+        assert!(!board.in_bounds(0, -1));
+
+        assert!(!Board::default().in_bounds(0, 0));
+    }
+
     #[test]
     fn test_adjacent_cells() {
         let game = SweeperGame::new(10, 10, 0);
@@ -286,25 +1266,23 @@ mod tests {
         let mut game = SweeperGame::new(10, 10, 0);
 
         // Bombs
-        for i in [10, 11] {
-            game.board.cells[i].is_bomb = true;
-            game.num_bombs += 1;
-        }
+        game.place_bombs(&[(0, 1), (1, 1)]).unwrap();
         game.start();
 
         assert_eq!(game.open(0, 0), GameState::Running);
         assert_eq!(game.num_revealed, 1);
-        assert_eq!(game.board.cells[0].is_revealed, true);
+        assert!(game.board.cells[0].is_revealed);
         assert_eq!(game.board.cells[0].mine_count, 2);
     }
 
     #[test]
     fn test_open_bomb() {
         let mut game = SweeperGame::new(10, 10, 0);
-        game.board.cells[0].is_bomb = true;
+        game.place_bombs(&[(0, 0)]).unwrap();
         game.start();
 
         assert_eq!(game.open(0, 0), GameState::Lose);
+        assert_eq!(game.last_opened, Some(0));
     }
 
     #[test]
@@ -315,21 +1293,18 @@ mod tests {
         // 0 1 .
         // 2 3 x
         // x x .
-        for i in [20, 21, 12] {
-            game.board.cells[i].is_bomb = true;
-            game.num_bombs += 1;
-        }
+        game.place_bombs(&[(0, 2), (1, 2), (2, 1)]).unwrap();
         game.start();
 
         assert_eq!(game.open(0, 0), GameState::Running);
         assert_eq!(game.num_revealed, 4);
-        assert_eq!(game.board.cells[0].is_revealed, true);
+        assert!(game.board.cells[0].is_revealed);
         assert_eq!(game.board.cells[0].mine_count, 0);
-        assert_eq!(game.board.cells[1].is_revealed, true);
+        assert!(game.board.cells[1].is_revealed);
         assert_eq!(game.board.cells[1].mine_count, 1);
-        assert_eq!(game.board.cells[10].is_revealed, true);
+        assert!(game.board.cells[10].is_revealed);
         assert_eq!(game.board.cells[10].mine_count, 2);
-        assert_eq!(game.board.cells[11].is_revealed, true);
+        assert!(game.board.cells[11].is_revealed);
         assert_eq!(game.board.cells[11].mine_count, 3);
     }
 
@@ -341,10 +1316,7 @@ mod tests {
         // 1 F .
         // x . .
         // . . .
-        for i in [10] {
-            game.board.cells[i].is_bomb = true;
-            game.num_bombs += 1;
-        }
+        game.place_bombs(&[(0, 1)]).unwrap();
         game.start();
 
         assert_eq!(game.open(0, 0), GameState::Running);
@@ -352,9 +1324,9 @@ mod tests {
         assert_eq!(game.open(0, 0), GameState::Lose);
 
         assert_eq!(game.num_revealed, 2);
-        assert_eq!(game.board.cells[0].is_revealed, true);
-        assert_eq!(game.board.cells[1].is_revealed, false);
-        assert_eq!(game.board.cells[10].is_revealed, true);
+        assert!(game.board.cells[0].is_revealed);
+        assert!(!game.board.cells[1].is_revealed);
+        assert!(game.board.cells[10].is_revealed);
     }
 
     #[test]
@@ -365,10 +1337,1225 @@ mod tests {
         // x 1 0
         // 1 1 0
         // 0 0 0
-        game.board.cells[0].is_bomb = true;
-        game.num_bombs = 1;
+        game.place_bombs(&[(0, 0)]).unwrap();
         game.start();
 
         assert_eq!(game.open(2, 2), GameState::Win);
     }
+
+    #[test]
+    fn test_zero_bomb_instant_win() {
+        // Without generating or starting
+        let mut game = SweeperGame::new(4, 3, 0);
+        assert_eq!(game.open(3, 2), GameState::Win);
+        assert_eq!(game.num_revealed, 12);
+        assert!(game.board.cells.iter().all(|cell| cell.is_revealed));
+
+        // Through the app's path
+        let mut game = SweeperGame::new(4, 3, 0);
+        game.generate_board(1, 1).unwrap();
+        game.start();
+        let result = game.open_with_result(1, 1);
+        assert_eq!(result.state, GameState::Win);
+        assert_eq!(result.revealed, 12);
+        assert!(result.pending.is_none());
+        assert!(game.board.cells.iter().all(|cell| cell.is_revealed));
+    }
+
+    #[test]
+    fn test_describe() {
+        let mut game = SweeperGame::new(3, 3, 0);
+
+        // Layout
+        // x 1 .
+        // 1 1 .
+        // . . x
+        game.place_bombs(&[(0, 0), (2, 2)]).unwrap();
+        assert_eq!(
+            game.describe_status(),
+            "Not started. 2 mines remaining, 0 flags placed, 0 seconds elapsed."
+        );
+
+        game.start();
+        game.open(1, 0);
+        game.open(0, 2);
+        game.flag(0, 0);
+        assert_eq!(
+            game.describe_cursor(1, 0),
+            "Column 2, row 1: 1 adjacent mine."
+        );
+        assert_eq!(
+            game.describe_cursor(1, 1),
+            "Column 2, row 2: 2 adjacent mines."
+        );
+        assert_eq!(game.describe_cursor(0, 2), "Column 1, row 3: empty.");
+        assert_eq!(game.describe_cursor(0, 0), "Column 1, row 1: flagged.");
+        assert_eq!(game.describe_cursor(2, 0), "Column 3, row 1: hidden.");
+        assert_eq!(
+            game.describe_cursor(3, 0),
+            "Column 4, row 1 is outside the board."
+        );
+        assert!(game
+            .describe_status()
+            .starts_with("In progress. 1 mine remaining, 1 flag placed, "));
+
+        game.open(2, 2);
+        assert_eq!(game.describe_cursor(2, 2), "Column 3, row 3: mine.");
+        assert!(game.describe_status().starts_with("Lost. "));
+    }
+
+    #[test]
+    fn test_display_kind() {
+        let cell = |is_bomb, is_flagged, is_revealed| Cell {
+            is_bomb,
+            is_flagged,
+            is_revealed,
+            mine_count: 3,
+        };
+
+        // (bomb, flagged, revealed), running, game over
+        let cases = [
+            (
+                (false, false, false),
+                CellDisplay::Hidden,
+                CellDisplay::Hidden,
+            ),
+            (
+                (false, true, false),
+                CellDisplay::Flagged,
+                CellDisplay::Flagged,
+            ),
+            (
+                (false, false, true),
+                CellDisplay::Revealed(3),
+                CellDisplay::Revealed(3),
+            ),
+            ((true, false, false), CellDisplay::Hidden, CellDisplay::Bomb),
+            ((true, true, false), CellDisplay::Flagged, CellDisplay::Bomb),
+            (
+                (true, false, true),
+                CellDisplay::Revealed(3),
+                CellDisplay::Bomb,
+            ),
+        ];
+        for ((is_bomb, is_flagged, is_revealed), running, game_over) in cases {
+            let cell = cell(is_bomb, is_flagged, is_revealed);
+            assert_eq!(cell.display_kind(false), running);
+            assert_eq!(cell.display_kind(true), game_over);
+        }
+    }
+
+    #[test]
+    fn test_open_result() {
+        let mut game = SweeperGame::new(10, 10, 0);
+
+        // Layout
+        // 1 F .
+        // x . .
+        // . . .
+        game.place_bombs(&[(0, 1)]).unwrap();
+        game.start();
+
+        let result = game.open_with_result(0, 0);
+        assert_eq!(result.state, GameState::Running);
+        assert_eq!(result.revealed, 1);
+
+        // Chording with an unsatisfied number does nothing
+        assert!(game.open_with_result(0, 0).is_noop());
+
+        // Opening a flagged cell does nothing
+        game.flag(1, 0);
+        let result = game.open_with_result(1, 0);
+        assert_eq!(result.state, GameState::Running);
+        assert_eq!(result.revealed, 0);
+        assert!(!game.board.cells[1].is_revealed);
+    }
+
+    #[test]
+    fn test_display() {
+        let mut game = SweeperGame::new(3, 3, 0);
+
+        // Layout
+        // x 1 .
+        // 1 1 .
+        // . . .
+        game.place_bombs(&[(0, 0)]).unwrap();
+        game.start();
+
+        assert_eq!(
+            game.to_string(),
+            "Running bombs: 1 flags: 0\n###\n###\n###\n"
+        );
+
+        game.open(1, 0);
+        game.flag(0, 0);
+        assert_eq!(
+            game.to_string(),
+            "Running bombs: 1 flags: 1\nF1#\n###\n###\n"
+        );
+
+        game.open(2, 2);
+        assert_eq!(game.to_string(), "Win bombs: 1 flags: 1\nF1.\n11.\n...\n");
+        assert_eq!(game.to_ascii(RenderStyle::Visible), "F1.\n11.\n...");
+    }
+
+    #[test]
+    fn test_auto_flag_on_win() {
+        let mut game = SweeperGame::new(10, 10, 0);
+        game.auto_flag_on_win = true;
+
+        // Layout
+        // x 1 .
+        // 1 1 .
+        // . . x
+        game.place_bombs(&[(0, 0), (9, 9)]).unwrap();
+        game.start();
+
+        game.flag(0, 0);
+        assert_eq!(game.open(5, 5), GameState::Win);
+        assert!(game
+            .board
+            .cells
+            .iter()
+            .filter(|cell| cell.is_bomb)
+            .all(|cell| cell.is_flagged));
+        assert_eq!(game.num_flags, game.num_bombs);
+    }
+
+    #[test]
+    fn test_auto_flag_after_wrong_flag() {
+        let mut game = SweeperGame::new(4, 1, 0);
+        game.auto_flag_on_win = true;
+        game.flag_budget = Some(1);
+
+        // Layout
+        // x 1 F x
+        game.place_bombs(&[(0, 0), (3, 0)]).unwrap();
+        game.start();
+
+        // The wrong flag uses up the budget and keeps its cell from being opened
+        assert_eq!(game.flag(2, 0), FlagResult::Flagged);
+        assert_eq!(game.open(1, 0), GameState::Running);
+        assert_eq!(game.open(2, 0), GameState::Running);
+        assert!(!game.board.cells[2].is_revealed);
+
+        // Opening the rest wins, leaving flags on exactly the bombs
+        assert_eq!(game.flag(2, 0), FlagResult::Unflagged);
+        assert_eq!(game.open(2, 0), GameState::Win);
+        assert!(game
+            .board
+            .cells
+            .iter()
+            .all(|cell| cell.is_flagged == cell.is_bomb));
+        assert_eq!(game.num_flags, game.num_bombs);
+        // The budget does not hold back the solution
+        assert_eq!(game.flags_left(), Some(0));
+    }
+
+    #[test]
+    fn test_capped_reveal() {
+        let layout = |game: &mut SweeperGame| {
+            game.place_bombs(&[(25, 1), (0, 7), (9, 13), (20, 20), (21, 20), (20, 29)])
+                .unwrap();
+            game.start();
+        };
+
+        let mut uncapped = SweeperGame::new(30, 30, 0);
+        layout(&mut uncapped);
+        let uncapped_result = uncapped.open_with_result(0, 0);
+        assert!(uncapped_result.pending.is_none());
+
+        let mut capped = SweeperGame::new(30, 30, 0);
+        capped.max_reveal_per_call = Some(50);
+        layout(&mut capped);
+        let mut result = capped.open_with_result(0, 0);
+        let mut calls = 1;
+        let mut total = result.revealed;
+        while let Some(token) = result.pending.take() {
+            assert!(result.revealed <= 50);
+            result = capped.resume_reveal(token);
+            total += result.revealed;
+            calls += 1;
+        }
+
+        assert!(calls > 1);
+        assert_eq!(total, uncapped_result.revealed);
+        assert_eq!(result.state, uncapped_result.state);
+        assert_eq!(capped.num_revealed, uncapped.num_revealed);
+        for (a, b) in capped.board.cells.iter().zip(&uncapped.board.cells) {
+            assert_eq!(a.is_revealed, b.is_revealed);
+            assert_eq!(a.mine_count, b.mine_count);
+        }
+    }
+
+    #[test]
+    fn test_neighbor_mine_count() {
+        let mut game = SweeperGame::new(10, 10, 0);
+
+        // Bombs
+        // 0 1 .
+        // 2 3 x
+        // x x .
+        game.place_bombs(&[(0, 2), (1, 2), (2, 1)]).unwrap();
+        game.start();
+
+        assert_eq!(game.neighbor_mine_count(0, 0), 0);
+        assert_eq!(game.neighbor_mine_count(1, 0), 1);
+        assert_eq!(game.neighbor_mine_count(0, 1), 2);
+        assert_eq!(game.neighbor_mine_count(1, 1), 3);
+        assert_eq!(game.neighbor_mine_count(-1, 0), 0);
+        assert_eq!(game.revealed_number(1, 1), None);
+
+        game.open(0, 0);
+        assert_eq!(game.revealed_number(0, 0), Some(0));
+        assert_eq!(game.revealed_number(1, 1), Some(3));
+        assert_eq!(game.revealed_number(2, 1), None);
+        assert_eq!(game.revealed_number(3, 3), None);
+    }
+
+    #[test]
+    fn test_three_bv() {
+        // Layout
+        // x 1 .
+        // 1 1 .
+        // . . .
+        let mut game = SweeperGame::new(3, 3, 0);
+        game.place_bombs(&[(0, 0)]).unwrap();
+        assert_eq!(game.three_bv(), 1);
+
+        // Layout
+        // 1 x 1
+        let mut game = SweeperGame::new(3, 1, 0);
+        game.place_bombs(&[(1, 0)]).unwrap();
+        assert_eq!(game.three_bv(), 2);
+
+        // Layout
+        // . 1 x 1 .
+        let mut game = SweeperGame::new(5, 1, 0);
+        game.place_bombs(&[(2, 0)]).unwrap();
+        assert_eq!(game.three_bv(), 2);
+    }
+
+    #[test]
+    fn test_abandon_hides_bombs() {
+        let mut game = SweeperGame::new(10, 10, 0);
+        game.place_bombs(&[(0, 1), (1, 1)]).unwrap();
+        game.start();
+        game.open(0, 0);
+
+        game.abandon();
+        assert_eq!(game.state, GameState::Abandoned);
+        assert!(!game.visible_board().contains(&CellDisplay::Bomb));
+        assert_eq!(game.visible_board()[0], CellDisplay::Revealed(2));
+
+        // Abandoning a finished game has no effect
+        game.state = GameState::Lose;
+        game.abandon();
+        assert_eq!(game.state, GameState::Lose);
+        assert_eq!(game.visible_board()[10], CellDisplay::Bomb);
+    }
+
+    #[test]
+    fn test_chord_win() {
+        let mut game = SweeperGame::new(3, 1, 0);
+
+        // Layout
+        // x 1 .
+        game.place_bombs(&[(0, 0)]).unwrap();
+        game.start();
+
+        assert_eq!(game.open(1, 0), GameState::Running);
+        game.flag(0, 0);
+        assert_eq!(game.open(1, 0), GameState::Win);
+    }
+
+    #[test]
+    fn test_chord_lose_not_win() {
+        let mut game = SweeperGame::new(3, 1, 0);
+
+        // Layout
+        // x 1 F
+        game.place_bombs(&[(0, 0)]).unwrap();
+        game.start();
+
+        assert_eq!(game.open(1, 0), GameState::Running);
+        game.flag(2, 0);
+        // Revealing the bomb brings num_revealed up to the safe cell count
+        assert_eq!(game.open(1, 0), GameState::Lose);
+        assert_eq!(game.num_revealed, 2);
+        assert_eq!(game.last_opened, Some(0));
+    }
+
+    #[test]
+    fn test_chord_into_bomb_counts() {
+        let mut game = SweeperGame::new(3, 3, 0);
+
+        // Layout
+        // F . x
+        // . 2 .
+        // x . F
+        game.place_bombs(&[(2, 0), (0, 2)]).unwrap();
+        game.start();
+        game.open(1, 1);
+        game.flag(0, 0);
+        game.flag(2, 2);
+
+        // The top middle cell is revealed before the chord reaches the top right bomb
+        assert_eq!(game.chord(1, 1).state, GameState::Lose);
+        assert_eq!(game.last_opened, Some(2));
+        let cell = game.get_cell(1, 0).unwrap();
+        assert!(cell.is_revealed);
+        assert_eq!(cell.mine_count, 1);
+        assert_eq!(game.get_cell(1, 1).unwrap().mine_count, 2);
+    }
+
+    #[test]
+    fn test_open_ordered() {
+        let board = || {
+            let mut game = SweeperGame::new(4, 3, 0);
+
+            // Layout
+            // . . . .
+            // . . 1 1
+            // . . 1 x
+            game.place_bombs(&[(3, 2)]).unwrap();
+            game.start();
+            game
+        };
+
+        let mut game = board();
+        let (state, order) = game.open_ordered(0, 0);
+        assert_eq!(state, GameState::Win);
+        assert_eq!(order, vec![0, 1, 4, 5, 2, 6, 8, 9, 10, 3, 7]);
+        assert_eq!(order.len(), game.num_revealed);
+
+        // Same board and click, same sequence
+        assert_eq!(board().open_ordered(0, 0).1, order);
+        let mut capped = board();
+        capped.max_reveal_per_call = Some(3);
+        assert_eq!(capped.open_ordered(0, 0).1, order);
+
+        // Recording stops after the call
+        let mut game = board();
+        game.open_ordered(3, 1);
+        assert_eq!(game.open_ordered(0, 0).1.len(), 10);
+        assert!(game.reveal_order.is_none());
+    }
+
+    #[test]
+    fn test_true_remaining_bombs() {
+        let mut game = SweeperGame::new(3, 3, 0);
+        game.place_bombs(&[(0, 0), (2, 2)]).unwrap();
+        game.start();
+        let naive = |game: &SweeperGame| game.num_bombs as isize - game.num_flags as isize;
+        assert_eq!(game.true_remaining_bombs(), 2);
+
+        game.flag(0, 0);
+        assert_eq!(game.true_remaining_bombs(), 1);
+        assert_eq!(naive(&game), 1);
+
+        // A misplaced flag fools the classic counter only
+        game.flag(1, 1);
+        assert_eq!(game.true_remaining_bombs(), 1);
+        assert_eq!(naive(&game), 0);
+
+        game.flag(2, 2);
+        assert_eq!(game.true_remaining_bombs(), 0);
+        assert_eq!(naive(&game), -1);
+    }
+
+    #[test]
+    fn test_clone_for_analysis() {
+        let mut game = SweeperGame::new(3, 3, 0);
+
+        // Layout
+        // . . .
+        // 1 2 1
+        // x # x
+        game.place_bombs(&[(0, 2), (2, 2)]).unwrap();
+        game.flag_budget = Some(1);
+        game.start();
+        game.open(0, 0);
+        game.on_state_change = Some(Box::new(|_, _| panic!("hook called from the clone")));
+        let before = game.to_bytes();
+
+        let mut analysis = game.clone_for_analysis();
+        assert_eq!(analysis.state, GameState::Running);
+        assert_eq!(analysis.start_time, None);
+        assert_eq!(analysis.get_elapsed_time(), Duration::ZERO);
+        assert_eq!(analysis.num_revealed, 6);
+
+        assert_eq!(analysis.flag(0, 2), FlagResult::Flagged);
+        assert_eq!(analysis.flag(2, 2), FlagResult::Flagged);
+        assert_eq!(analysis.open(1, 2), GameState::Win);
+        assert_eq!(game.to_bytes(), before);
+        assert_eq!(game.state, GameState::Running);
+        assert!(game.start_time.is_some());
+    }
+
+    #[test]
+    fn test_flags_match_bombs() {
+        let new_game = || {
+            let mut game = SweeperGame::new(3, 3, 0);
+
+            // Layout
+            // x 1 .
+            // 1 1 .
+            // . . .
+            game.place_bombs(&[(0, 0)]).unwrap();
+            game.start();
+            game
+        };
+
+        let mut game = new_game();
+        assert!(!game.flags_match_bombs());
+        game.flag(0, 0);
+        assert!(game.flags_match_bombs());
+        assert_eq!(game.open(2, 2), GameState::Win);
+        assert!(game.flags_match_bombs());
+
+        // Won by revealing, without flagging the bomb
+        let mut game = new_game();
+        game.flag(1, 1);
+        assert!(!game.flags_match_bombs());
+        game.flag(1, 1);
+        assert_eq!(game.open(2, 2), GameState::Win);
+        assert!(!game.flags_match_bombs());
+        assert!(!game.won_with_perfect_flags());
+    }
+
+    #[test]
+    fn test_won_with_perfect_flags() {
+        for auto_flag_on_win in [false, true] {
+            for flag_bomb in [false, true] {
+                let mut game = SweeperGame::new(3, 1, 0);
+                game.auto_flag_on_win = auto_flag_on_win;
+
+                // Layout
+                // x 1 .
+                game.place_bombs(&[(0, 0)]).unwrap();
+                game.start();
+                if flag_bomb {
+                    game.flag(0, 0);
+                }
+                assert!(!game.won_with_perfect_flags());
+                assert_eq!(game.open(2, 0), GameState::Win);
+                assert_eq!(game.won_with_perfect_flags(), flag_bomb);
+                assert_eq!(game.flags_match_bombs(), flag_bomb || auto_flag_on_win);
+
+                game.reset();
+                assert!(!game.won_with_perfect_flags());
+            }
+        }
+    }
+
+    #[test]
+    fn test_reveal_after_game_over() {
+        let mut game = SweeperGame::new(3, 1, 0);
+        game.auto_flag_on_win = true;
+
+        // Layout
+        // x 1 .
+        game.place_bombs(&[(0, 0)]).unwrap();
+        game.start();
+        assert_eq!(game.open(2, 0), GameState::Win);
+        let total_time = game.total_time;
+        std::thread::sleep(Duration::from_millis(5));
+
+        // Reveals after the win change nothing, and the star is decided only once
+        assert!(game.open_with_result(1, 0).is_noop());
+        assert!(game.chord(1, 0).is_noop());
+        assert!(game.open_with_result(0, 0).is_noop());
+        assert_eq!(game.state, GameState::Win);
+        assert!(!game.won_with_perfect_flags());
+        assert_eq!(game.total_time, total_time);
+
+        // The same goes after a loss
+        game.reset();
+        game.place_bombs(&[(0, 0)]).unwrap();
+        game.start();
+        assert_eq!(game.open(0, 0), GameState::Lose);
+        assert!(game.open_with_result(2, 0).is_noop());
+        assert_eq!(game.num_revealed, 1);
+    }
+
+    #[test]
+    fn test_recount() {
+        let mut game = SweeperGame::new(10, 10, 0);
+        game.place_bombs(&[(0, 0), (1, 1), (5, 5), (9, 9)]).unwrap();
+        game.start();
+        game.flag(0, 0);
+        game.flag(1, 1);
+        game.flag(2, 2);
+        game.flag(2, 2);
+        game.open(9, 0);
+        game.open(1, 0);
+        game.open(5, 6);
+        game.flag(5, 5);
+        game.chord(5, 6);
+
+        let (num_flags, num_revealed) = (game.num_flags, game.num_revealed);
+        game.recount();
+        assert_eq!(game.num_flags, num_flags);
+        assert_eq!(game.num_revealed, num_revealed);
+        assert_eq!(num_flags, 3);
+
+        // Counters drift when cells are edited directly
+        game.board.cells[99].is_flagged = true;
+        game.board.cells[98].is_revealed = false;
+        game.recount();
+        assert_eq!(game.num_flags, num_flags + 1);
+        assert_eq!(game.num_revealed, num_revealed - 1);
+    }
+
+    #[test]
+    fn test_reveal_progress() {
+        let mut game = SweeperGame::new(4, 3, 0);
+
+        // Layout
+        // . . 1 x
+        // . . 1 1
+        // . . . .
+        game.place_bombs(&[(3, 0)]).unwrap();
+        game.start();
+        assert_eq!(game.reveal_progress(), 0.0);
+
+        game.open(3, 1);
+        assert_eq!(game.reveal_progress(), 1.0 / 11.0);
+
+        game.open(0, 0);
+        assert_eq!(game.state, GameState::Win);
+        assert_eq!(game.reveal_progress(), 1.0);
+
+        let mut game = SweeperGame::new(2, 1, 0);
+        game.place_bombs(&[(0, 0), (1, 0)]).unwrap();
+        assert_eq!(game.reveal_progress(), 1.0);
+    }
+
+    #[test]
+    fn test_place_bombs() {
+        let mut game = SweeperGame::new(4, 3, 5);
+        assert_eq!(game.place_bombs(&[(1, 0), (3, 2), (1, 0)]), Ok(()));
+        assert_eq!(game.num_bombs, 2);
+        let bombs: Vec<usize> = (0..12).filter(|&i| game.board.cells[i].is_bomb).collect();
+        assert_eq!(bombs, vec![1, 11]);
+
+        // Placing again replaces the layout; invalid coordinates are skipped
+        assert_eq!(
+            game.place_bombs(&[(0, 0), (4, 0), (2, 1), (-1, 2)]),
+            Err(GenerateError::InvalidCoordinate(4, 0))
+        );
+        assert_eq!(game.num_bombs, 2);
+        let bombs: Vec<usize> = (0..12).filter(|&i| game.board.cells[i].is_bomb).collect();
+        assert_eq!(bombs, vec![0, 6]);
+
+        game.start();
+        assert_eq!(
+            game.place_bombs(&[(3, 2)]),
+            Err(GenerateError::AlreadyStarted)
+        );
+        assert_eq!(game.num_bombs, 2);
+        assert!(!game.board.cells[11].is_bomb);
+    }
+
+    #[test]
+    fn test_density() {
+        let mut game = SweeperGame::new(10, 10, 0);
+        assert_eq!(game.board.density(), 0.0);
+        for i in 0..10 {
+            game.board.cells[i].is_bomb = true;
+        }
+        assert_eq!(game.board.density(), 0.1);
+        assert_eq!(Board::default().density(), 0.0);
+
+        assert_eq!(bomb_count_for_density(10, 10, 0.0), 0);
+        assert_eq!(bomb_count_for_density(10, 10, 1.0), 99);
+        assert_eq!(bomb_count_for_density(10, 10, 2.0), 99);
+    }
+
+    #[test]
+    fn test_daily() {
+        let bombs = |date| {
+            let mut game = SweeperGame::daily(16, 16, 40, date);
+            game.generate_board(3, 4).unwrap();
+            game.board
+                .cells
+                .iter()
+                .map(|cell| cell.is_bomb)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(bombs((2024, 5, 17)), bombs((2024, 5, 17)));
+        assert_ne!(bombs((2024, 5, 17)), bombs((2024, 5, 18)));
+        assert!(!bombs((2024, 5, 17))[4 * 16 + 3]);
+        assert_eq!(daily_seed((2024, 5, 17)), daily_seed((2024, 5, 17)));
+
+        // Pinned, so the daily board can't silently change between builds
+        let mut game = SweeperGame::daily(9, 9, 10, (2024, 5, 17));
+        game.generate_board(4, 4).unwrap();
+        let layout: Vec<usize> = (0..81).filter(|&i| game.board.cells[i].is_bomb).collect();
+        assert_eq!(layout, [11, 13, 21, 27, 38, 43, 70, 74, 78, 80]);
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut game = SweeperGame::new(10, 10, 20);
+        game.generate_board(0, 0).unwrap();
+        game.start();
+        game.open(0, 0);
+        game.flag(9, 9);
+
+        game.reset();
+        assert_eq!(game.state, GameState::NotRunning);
+        assert_eq!(game.get_width(), 10);
+        assert_eq!(game.get_height(), 10);
+        assert_eq!(game.num_bombs, 20);
+        assert_eq!(game.num_revealed, 0);
+        assert_eq!(game.num_flags, 0);
+        assert_eq!(game.start_time, None);
+        assert_eq!(game.get_elapsed_time(), Duration::ZERO);
+        assert!(game
+            .board
+            .cells
+            .iter()
+            .all(|cell| !cell.is_bomb && !cell.is_flagged && !cell.is_revealed));
+
+        game.generate_board(5, 5).unwrap();
+        let num_bombs = game.board.cells.iter().filter(|&cell| cell.is_bomb).count();
+        assert_eq!(num_bombs, 20);
+    }
+
+    #[test]
+    fn test_render_into() {
+        let mut game = SweeperGame::new(12, 7, 0);
+        game.place_bombs(&[(3, 0), (8, 1), (5, 3), (6, 3), (8, 6)])
+            .unwrap();
+        game.start();
+        game.open(11, 6);
+        game.flag(3, 0);
+
+        let mut buf = String::from("stale contents");
+        for style in [RenderStyle::Visible, RenderStyle::Revealed] {
+            game.render_into(&mut buf, style);
+            assert_eq!(buf, game.to_ascii(style));
+        }
+        assert!(!game.to_ascii(RenderStyle::Visible).contains('*'));
+        assert_eq!(game.to_ascii(RenderStyle::Revealed).matches('*').count(), 5);
+    }
+
+    #[test]
+    #[ignore = "benchmark, run with --ignored --nocapture"]
+    fn bench_render_into() {
+        let mut game = SweeperGame::new(200, 200, 4000);
+        game.generate_board(100, 100).unwrap();
+        game.start();
+        game.open(100, 100);
+
+        let iterations = 100;
+        let start = Instant::now();
+        for _ in 0..iterations {
+            std::hint::black_box(game.to_ascii(RenderStyle::Visible));
+        }
+        let to_ascii_time = start.elapsed();
+
+        let mut buf = String::new();
+        let start = Instant::now();
+        for _ in 0..iterations {
+            game.render_into(&mut buf, RenderStyle::Visible);
+            std::hint::black_box(&buf);
+        }
+        let render_into_time = start.elapsed();
+
+        println!("to_ascii: {:?}/iter", to_ascii_time / iterations);
+        println!("render_into: {:?}/iter", render_into_time / iterations);
+    }
+
+    #[test]
+    fn test_flag_result() {
+        let mut game = SweeperGame::new(10, 10, 0);
+        game.place_bombs(&[(0, 1)]).unwrap();
+        game.start();
+        game.open(0, 0);
+
+        // Unrevealed
+        assert_eq!(game.flag(1, 0), FlagResult::Flagged);
+        assert_eq!(game.num_flags, 1);
+        assert_eq!(game.flag(1, 0), FlagResult::Unflagged);
+        assert_eq!(game.num_flags, 0);
+
+        // Revealed
+        assert_eq!(game.flag(0, 0), FlagResult::Ignored);
+        assert!(!game.board.cells[0].is_flagged);
+
+        // Out of bounds
+        assert_eq!(game.flag(-1, 0), FlagResult::Ignored);
+        assert_eq!(game.num_flags, 0);
+    }
+
+    #[test]
+    fn test_game_state_classification() {
+        for (state, terminal) in [
+            (GameState::NotRunning, false),
+            (GameState::Running, false),
+            (GameState::Win, true),
+            (GameState::Lose, true),
+            (GameState::Abandoned, true),
+        ] {
+            assert_eq!(state.is_terminal(), terminal, "{state:?}");
+            assert_eq!(state.is_active(), !terminal, "{state:?}");
+        }
+    }
+
+    #[test]
+    fn test_on_state_change() {
+        use std::{cell::RefCell, rc::Rc};
+
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        let mut game = SweeperGame::new(10, 10, 0);
+        game.place_bombs(&[(0, 0)]).unwrap();
+        let recorded = Rc::clone(&changes);
+        game.on_state_change = Some(Box::new(move |old, new| {
+            recorded.borrow_mut().push((old, new))
+        }));
+
+        game.start();
+        changes.borrow_mut().clear();
+
+        assert_eq!(game.open(0, 0), GameState::Lose);
+        assert_eq!(
+            *changes.borrow(),
+            vec![(GameState::Running, GameState::Lose)]
+        );
+
+        // Same-to-same assignments don't notify
+        game.resign();
+        game.set_state(GameState::Lose);
+        assert_eq!(changes.borrow().len(), 1);
+    }
+
+    #[test]
+    fn test_flag_budget() {
+        let mut game = SweeperGame::new(10, 10, 5);
+        game.flag_budget = Some(2);
+        game.start();
+        assert_eq!(game.flags_left(), Some(2));
+
+        assert_eq!(game.flag(0, 0), FlagResult::Flagged);
+        assert_eq!(game.flag(1, 0), FlagResult::Flagged);
+        assert_eq!(game.flags_left(), Some(0));
+
+        // Over budget
+        assert_eq!(game.flag(2, 0), FlagResult::Ignored);
+        assert!(!game.board.cells[2].is_flagged);
+        assert_eq!(game.num_flags, 2);
+
+        // Unflagging frees budget
+        assert_eq!(game.flag(0, 0), FlagResult::Unflagged);
+        assert_eq!(game.flags_left(), Some(1));
+        assert_eq!(game.flag(2, 0), FlagResult::Flagged);
+    }
+
+    #[test]
+    fn test_iter_frontier() {
+        let mut game = SweeperGame::new(4, 3, 0);
+
+        // Layout after opening the top-left corner
+        // . 1 x #
+        // . 1 2 x
+        // . . 1 #
+        game.place_bombs(&[(2, 0), (3, 1)]).unwrap();
+        game.start();
+        game.open(0, 0);
+        assert_eq!(game.num_revealed, 8);
+
+        let frontier: Vec<_> = game.iter_frontier().collect();
+        assert_eq!(
+            frontier,
+            vec![
+                (1, vec![2]),
+                (5, vec![2]),
+                (6, vec![2, 3, 7, 11]),
+                (10, vec![7, 11]),
+            ]
+        );
+
+        // A satisfied number with no unknown neighbors drops out
+        game.flag(2, 0);
+        let frontier: Vec<_> = game.iter_frontier().collect();
+        assert_eq!(frontier, vec![(6, vec![3, 7, 11]), (10, vec![7, 11])]);
+    }
+
+    #[test]
+    fn test_is_number_satisfied() {
+        let mut game = SweeperGame::new(10, 10, 0);
+
+        // Layout
+        // 0 1 .
+        // 2 3 x
+        // x x .
+        game.place_bombs(&[(0, 2), (1, 2), (2, 1)]).unwrap();
+        game.start();
+        game.open(0, 0);
+
+        assert!(!game.is_number_satisfied(1, 0));
+        game.flag(2, 1);
+        assert!(game.is_number_satisfied(1, 0));
+        assert!(!game.is_number_satisfied(1, 1));
+
+        // Zero and unrevealed cells are never satisfied
+        assert!(!game.is_number_satisfied(0, 0));
+        assert!(!game.is_number_satisfied(5, 5));
+        assert!(!game.is_number_satisfied(-1, 0));
+    }
+
+    #[test]
+    fn test_generate_invalid_coordinate() {
+        let mut game = SweeperGame::new(10, 10, 20);
+        assert_eq!(
+            game.generate_board(-1, -1),
+            Err(GenerateError::InvalidCoordinate(-1, -1))
+        );
+        assert_eq!(
+            game.generate_board(10, 0),
+            Err(GenerateError::InvalidCoordinate(10, 0))
+        );
+        assert!(game.board.cells.iter().all(|cell| !cell.is_bomb));
+    }
+
+    #[test]
+    fn test_generate_first_click_safe() {
+        for seed in 0..50 {
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+
+            // Room for every bomb away from the click
+            let mut game = SweeperGame::new(9, 9, 60);
+            game.generate_board_with_rng(4, 4, &mut rng).unwrap();
+            assert_eq!(game.num_bombs, 60);
+            assert_eq!(game.neighbor_mine_count(4, 4), 0);
+            assert!(!game.get_cell(4, 4).unwrap().is_bomb);
+
+            // Some bombs have to go next to the click, never on it
+            let mut game = SweeperGame::new(9, 9, 78);
+            game.generate_board_with_rng(0, 8, &mut rng).unwrap();
+            assert_eq!(game.num_bombs, 78);
+            assert!(!game.get_cell(0, 8).unwrap().is_bomb);
+            assert_eq!(game.neighbor_mine_count(0, 8), 1);
+        }
+    }
+
+    #[test]
+    fn test_try_new() {
+        assert!(SweeperGame::try_new(30, 16, 99).is_ok());
+        assert_eq!(
+            SweeperGame::try_new(usize::MAX, 2, 0).unwrap_err(),
+            NewGameError::Overflow
+        );
+        assert_eq!(
+            SweeperGame::try_new(1 << 13, 1 << 12, 0).unwrap_err(),
+            NewGameError::TooManyCells {
+                cells: 1 << 25,
+                max_cells: MAX_CELLS
+            }
+        );
+        assert_eq!(
+            SweeperGame::try_new_with_max_cells(10, 10, 10, 99).unwrap_err(),
+            NewGameError::TooManyCells {
+                cells: 100,
+                max_cells: 99
+            }
+        );
+        assert!(SweeperGame::try_new_with_max_cells(10, 10, 10, 100).is_ok());
+        assert_eq!(
+            SweeperGame::try_new(3, 3, 9).unwrap_err(),
+            NewGameError::TooManyBombs { bombs: 9, cells: 9 }
+        );
+        assert_eq!(
+            SweeperGame::try_new(0, 0, 0).unwrap_err(),
+            NewGameError::TooManyBombs { bombs: 0, cells: 0 }
+        );
+        assert!(SweeperGame::try_new(3, 3, 8).is_ok());
+    }
+
+    #[test]
+    fn test_generate_near_capacity() {
+        let placed = |game: &SweeperGame| game.board.cells.iter().filter(|c| c.is_bomb).count();
+
+        let mut game = SweeperGame::new(3, 3, 8);
+        game.generate_board(1, 1).unwrap();
+        assert_eq!(placed(&game), 8);
+        assert_eq!(game.num_bombs, 8);
+        assert_eq!(game.open(1, 1), GameState::Win);
+
+        // Only eight cells can hold bombs
+        let mut game = SweeperGame::new(3, 3, 8);
+        game.num_bombs = 9;
+        game.generate_board(1, 1).unwrap();
+        assert_eq!(placed(&game), 8);
+        assert_eq!(game.num_bombs, 8);
+        assert_eq!(game.open(1, 1), GameState::Win);
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let fresh = SweeperGame::new(10, 10, 10);
+
+        let mut running = SweeperGame::new(16, 16, 40);
+        running.generate_board(8, 8).unwrap();
+        running.start();
+        running.open(8, 8);
+        running.flag(0, 0);
+
+        let mut lost = SweeperGame::new(30, 16, 99);
+        lost.generate_board(0, 0).unwrap();
+        lost.start();
+        let bomb = lost
+            .board
+            .cells
+            .iter()
+            .position(|cell| cell.is_bomb)
+            .unwrap();
+        lost.open(bomb as isize % 30, bomb as isize / 30);
+
+        for game in [fresh, running, lost] {
+            let bytes = game.to_bytes();
+            let len = game.board.cells.len();
+            assert_eq!(bytes.len(), SAVE_HEADER_LEN + (len * 3).div_ceil(8));
+
+            let decoded = SweeperGame::from_bytes(&bytes).unwrap();
+            assert_eq!(decoded.get_width(), game.get_width());
+            assert_eq!(decoded.get_height(), game.get_height());
+            assert_eq!(decoded.num_bombs, game.num_bombs);
+            assert_eq!(decoded.num_revealed, game.num_revealed);
+            assert_eq!(decoded.num_flags, game.num_flags);
+            assert_eq!(decoded.state, game.state);
+            assert_eq!(decoded.to_string(), game.to_string());
+            assert_eq!(
+                decoded.to_ascii(RenderStyle::Revealed),
+                game.to_ascii(RenderStyle::Revealed)
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_bytes_errors() {
+        let bytes = SweeperGame::new(4, 4, 2).to_bytes();
+        assert_eq!(
+            SweeperGame::from_bytes(&bytes[..10]).unwrap_err(),
+            DecodeError::TooShort
+        );
+        assert_eq!(
+            SweeperGame::from_bytes(&bytes[..bytes.len() - 1]).unwrap_err(),
+            DecodeError::TooShort
+        );
+
+        let mut bad = bytes.clone();
+        bad[0] = b'X';
+        assert_eq!(
+            SweeperGame::from_bytes(&bad).unwrap_err(),
+            DecodeError::BadMagic
+        );
+
+        let mut bad = bytes.clone();
+        bad[2] = 9;
+        assert_eq!(
+            SweeperGame::from_bytes(&bad).unwrap_err(),
+            DecodeError::UnsupportedVersion(9)
+        );
+
+        let mut bad = bytes.clone();
+        bad[SAVE_HEADER_LEN - 1] = 7;
+        assert_eq!(
+            SweeperGame::from_bytes(&bad).unwrap_err(),
+            DecodeError::InvalidState(7)
+        );
+
+        let mut bad = bytes;
+        bad[SAVE_HEADER_LEN] = 0b010;
+        assert_eq!(
+            SweeperGame::from_bytes(&bad).unwrap_err(),
+            DecodeError::Inconsistent
+        );
+
+        // Bomb count beyond the board, or disagreeing with the placed bombs
+        let mut game = SweeperGame::new(4, 4, 2);
+        game.generate_board(0, 0).unwrap();
+        let bytes = game.to_bytes();
+        for num_bombs in [16u32, 1000, 3] {
+            let mut bad = bytes.clone();
+            bad[11..15].copy_from_slice(&num_bombs.to_le_bytes());
+            assert_eq!(
+                SweeperGame::from_bytes(&bad).unwrap_err(),
+                DecodeError::Inconsistent,
+                "{num_bombs} bombs"
+            );
+        }
+        assert!(SweeperGame::from_bytes(&bytes).is_ok());
+    }
+
+    #[test]
+    fn test_safe_cells_remaining() {
+        let mut game = SweeperGame::new(4, 3, 0);
+
+        // Layout after opening the top-left corner
+        // . 1 x #
+        // . 1 2 x
+        // . . 1 #
+        game.place_bombs(&[(2, 0), (3, 1)]).unwrap();
+        game.start();
+        assert_eq!(game.remaining_safe_cells(), 10);
+
+        game.open(0, 0);
+        let coords = game.safe_cells_remaining_coords();
+        assert_eq!(coords, vec![(3, 0), (3, 2)]);
+        assert_eq!(coords.len(), game.remaining_safe_cells());
+    }
+
+    #[test]
+    fn test_start_idempotent() {
+        let mut game = SweeperGame::new(3, 1, 0);
+        game.place_bombs(&[(0, 0)]).unwrap();
+        game.start();
+        let start_time = game.start_time;
+
+        game.start();
+        assert_eq!(game.start_time, start_time);
+        assert_eq!(game.state, GameState::Running);
+
+        for (x, state) in [(2, GameState::Win), (0, GameState::Lose)] {
+            let mut game = SweeperGame::new(3, 1, 0);
+            game.place_bombs(&[(0, 0)]).unwrap();
+            game.start();
+            assert_eq!(game.open(x, 0), state);
+            let start_time = game.start_time;
+            let total_time = game.total_time;
+
+            game.start();
+            assert_eq!(game.state, state);
+            assert_eq!(game.start_time, start_time);
+            assert_eq!(game.total_time, total_time);
+            assert_eq!(game.get_elapsed_time(), total_time);
+        }
+    }
+
+    #[test]
+    fn test_flood_through_diagonal_zeros() {
+        let mut game = SweeperGame::new(4, 4, 0);
+
+        // Layout
+        // 0 0 1 x
+        // 0 0 1 1
+        // 1 1 0 0
+        // x 1 0 0
+        // The zero regions only touch diagonally at (1, 1) and (2, 2)
+        game.place_bombs(&[(3, 0), (0, 3)]).unwrap();
+        game.start();
+
+        assert_eq!(game.open(0, 0), GameState::Win);
+        assert_eq!(game.num_revealed, 14);
+        assert!(game.board.cells[15].is_revealed);
+        assert_eq!(game.revealed_number(2, 1), Some(1));
+        assert_eq!(game.revealed_number(1, 2), Some(1));
+        assert_eq!(game.revealed_number(3, 3), Some(0));
+    }
+
+    #[test]
+    fn test_flood_stops_at_numbers() {
+        let mut game = SweeperGame::new(5, 3, 0);
+
+        // Layout
+        // 0 2 x 2 0
+        // 0 3 x 3 0
+        // 0 2 x 2 0
+        game.place_bombs(&[(2, 0), (2, 1), (2, 2)]).unwrap();
+        game.start();
+
+        assert_eq!(game.open(0, 0), GameState::Running);
+        assert_eq!(game.num_revealed, 6);
+        for y in 0..3 {
+            assert_eq!(game.revealed_number(0, y), Some(0));
+            assert!(game.revealed_number(1, y).unwrap() > 0);
+            for x in 2..5 {
+                assert!(!game.get_cell(x, y).unwrap().is_revealed);
+            }
+        }
+        assert_eq!(game.revealed_number(1, 1), Some(3));
+    }
+
+    #[test]
+    fn test_chord_preview() {
+        let mut game = SweeperGame::new(5, 3, 0);
+
+        // Layout
+        // 0 2 x 2 0
+        // 0 3 x 3 0
+        // 0 2 x 2 0
+        game.place_bombs(&[(2, 0), (2, 1), (2, 2)]).unwrap();
+        game.start();
+        game.open(0, 0);
+
+        // Not a revealed number
+        assert_eq!(game.chord_preview(0, 0), None);
+        assert_eq!(game.chord_preview(2, 0), None);
+        assert_eq!(game.chord_preview(-1, 0), None);
+
+        // Unsatisfied
+        game.flag(2, 0);
+        assert_eq!(game.chord_preview(1, 0), None);
+
+        // Satisfied with nothing left to open
+        game.flag(2, 1);
+        let before = game.to_ascii(RenderStyle::Visible);
+        assert_eq!(game.chord_preview(1, 0), Some(vec![]));
+        assert_eq!(game.to_ascii(RenderStyle::Visible), before);
+        assert_eq!(game.num_revealed, 6);
+    }
+
+    #[test]
+    fn test_chord_preview_misplaced_flag() {
+        let mut game = SweeperGame::new(3, 1, 0);
+
+        // Layout
+        // x 1 F
+        game.place_bombs(&[(0, 0)]).unwrap();
+        game.start();
+        game.open(1, 0);
+        game.flag(2, 0);
+
+        let preview = game.chord_preview(1, 0).unwrap();
+        assert_eq!(preview, vec![0]);
+        assert_eq!(game.chord(1, 0).state, GameState::Lose);
+        assert!(game.board.cells[0].is_revealed);
+        assert!(!game.board.cells[2].is_revealed);
+    }
+
+    #[test]
+    fn test_chord_preview_matches_chord() {
+        let mut game = SweeperGame::new(3, 3, 0);
+
+        // Layout
+        // x 2 x
+        // 1 2 1
+        // 0 0 0
+        game.place_bombs(&[(0, 0), (2, 0)]).unwrap();
+        game.start();
+        game.open(1, 0);
+        game.flag(0, 0);
+        game.flag(2, 0);
+
+        let preview = game.chord_preview(1, 0).unwrap();
+        assert_eq!(preview, vec![3, 4, 5]);
+        let before: Vec<bool> = game.board.cells.iter().map(|c| c.is_revealed).collect();
+        let result = game.chord(1, 0);
+        let revealed: Vec<usize> = (0..game.board.cells.len())
+            .filter(|&i| game.board.cells[i].is_revealed && !before[i])
+            .collect();
+        assert_eq!(revealed, preview);
+        assert_eq!(result.revealed, preview.len());
+        assert_eq!(result.state, GameState::Running);
+
+        // Chording a hidden cell does nothing
+        let mut game = SweeperGame::new(3, 3, 0);
+        game.start();
+        assert!(game.chord(1, 1).is_noop());
+    }
 }
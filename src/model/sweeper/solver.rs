@@ -0,0 +1,309 @@
+use super::{FlagResult, SweeperGame};
+
+/// Cells changed by the solver.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SolveProgress {
+    pub flagged: usize,
+    pub revealed: usize,
+}
+
+impl SolveProgress {
+    /// Whether nothing could be deduced.
+    pub fn is_stuck(&self) -> bool {
+        self.flagged == 0 && self.revealed == 0
+    }
+}
+
+impl SweeperGame {
+    /// One pass of single-point deductions over the frontier: flag the unknown neighbors of a
+    /// number that needs all of them, and open those of a number already satisfied by flags.
+    ///
+    /// Only revealed numbers and flags are used, so wrong flags can lead to opening a bomb.
+    pub fn solve_step(&mut self) -> SolveProgress {
+        let mut progress = SolveProgress::default();
+        if !self.state.is_active() {
+            return progress;
+        }
+
+        let mut to_flag = Vec::new();
+        let mut to_open = Vec::new();
+        for (i, unknown) in self.iter_frontier() {
+            let mine_count = self.board.cells[i].mine_count as usize;
+            let flags = self.adjacent_flag_count(i);
+            if flags == mine_count {
+                to_open.extend(unknown);
+            } else if flags < mine_count && mine_count - flags == unknown.len() {
+                to_flag.extend(unknown);
+            }
+        }
+
+        let width = self.board.width;
+        for i in to_flag {
+            let cell = &self.board.cells[i];
+            if !cell.is_flagged
+                && !cell.is_revealed
+                && self.flag((i % width) as isize, (i / width) as isize) == FlagResult::Flagged
+            {
+                progress.flagged += 1;
+            }
+        }
+        for i in to_open {
+            let cell = &self.board.cells[i];
+            if cell.is_flagged || cell.is_revealed || !self.state.is_active() {
+                continue;
+            }
+            let num_revealed = self.num_revealed;
+            self.open((i % width) as isize, (i / width) as isize);
+            progress.revealed += self.num_revealed - num_revealed;
+        }
+
+        progress
+    }
+
+    /// Whether the hidden, unflagged cell is safe by single-point deduction: a neighboring number
+    /// already has all its mines accounted for, either flagged or forced by some number whose
+    /// unknown neighbors must all be mines.
+    pub fn is_cursor_provably_safe(&self, x: isize, y: isize) -> bool {
+        let Some(index) = self.cell_index(x, y) else {
+            return false;
+        };
+        let cell = &self.board.cells[index];
+        if cell.is_revealed || cell.is_flagged {
+            return false;
+        }
+
+        let mut forced = vec![false; self.board.cells.len()];
+        for (i, unknown) in self.iter_frontier() {
+            let mines_left = (self.board.cells[i].mine_count as usize)
+                .saturating_sub(self.adjacent_flag_count(i));
+            if mines_left == unknown.len() {
+                for j in unknown {
+                    forced[j] = true;
+                }
+            }
+        }
+        if forced[index] {
+            return false;
+        }
+
+        self.iter_frontier()
+            .filter(|(_, unknown)| unknown.contains(&index))
+            .any(|(i, unknown)| {
+                let known_mines =
+                    self.adjacent_flag_count(i) + unknown.iter().filter(|&&j| forced[j]).count();
+                known_mines == self.board.cells[i].mine_count as usize
+            })
+    }
+
+    /// Number of unknown frontier cells that neither single-point nor subset deduction can
+    /// prove safe or a bomb. Flags are trusted.
+    ///
+    /// Subset deduction compares two numbers whose unknown neighbors contain one another: the
+    /// cells only the larger one sees hold the difference of their remaining mines.
+    pub fn undecidable_count(&self) -> usize {
+        // Unknown neighbors of each frontier number with its mines not yet flagged
+        let mut constraints: Vec<(Vec<usize>, usize)> = self
+            .iter_frontier()
+            .map(|(i, unknown)| {
+                let mines_left = (self.board.cells[i].mine_count as usize)
+                    .saturating_sub(self.adjacent_flag_count(i));
+                (unknown, mines_left)
+            })
+            .collect();
+        let mut frontier: Vec<usize> = constraints
+            .iter()
+            .flat_map(|(unknown, _)| unknown.iter().copied())
+            .collect();
+        frontier.sort_unstable();
+        frontier.dedup();
+
+        // Whether each cell is known to be a bomb
+        let mut known: Vec<Option<bool>> = vec![None; self.board.cells.len()];
+        loop {
+            for (unknown, mines_left) in constraints.iter_mut() {
+                unknown.retain(|&j| match known[j] {
+                    Some(is_bomb) => {
+                        *mines_left = mines_left.saturating_sub(is_bomb as usize);
+                        false
+                    }
+                    None => true,
+                });
+            }
+            constraints.retain(|(unknown, _)| !unknown.is_empty());
+
+            let mut deduced = Vec::new();
+            for (unknown, mines_left) in &constraints {
+                if *mines_left == 0 || *mines_left == unknown.len() {
+                    deduced.extend(unknown.iter().map(|&j| (j, *mines_left > 0)));
+                }
+                for (other, other_mines) in &constraints {
+                    if other.len() <= unknown.len()
+                        || other_mines < mines_left
+                        || !unknown.iter().all(|j| other.contains(j))
+                    {
+                        continue;
+                    }
+                    let rest: Vec<usize> = other
+                        .iter()
+                        .copied()
+                        .filter(|j| !unknown.contains(j))
+                        .collect();
+                    let rest_mines = other_mines - mines_left;
+                    if rest_mines == 0 || rest_mines == rest.len() {
+                        deduced.extend(rest.into_iter().map(|j| (j, rest_mines > 0)));
+                    }
+                }
+            }
+            if deduced.is_empty() {
+                break;
+            }
+            for (j, is_bomb) in deduced {
+                known[j] = Some(is_bomb);
+            }
+        }
+
+        frontier.into_iter().filter(|&j| known[j].is_none()).count()
+    }
+
+    /// Repeat [`Self::solve_step`] until it makes no more progress, never guessing.
+    pub fn solve_all(&mut self) -> SolveProgress {
+        let mut total = SolveProgress::default();
+        loop {
+            let progress = self.solve_step();
+            if progress.is_stuck() {
+                return total;
+            }
+            total.flagged += progress.flagged;
+            total.revealed += progress.revealed;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::sweeper::GameState;
+
+    #[test]
+    fn test_solve_all_deducible() {
+        let mut game = SweeperGame::new(3, 3, 0);
+
+        // Layout
+        // x x 1
+        // 2 2 1
+        // . . .
+        game.place_bombs(&[(0, 0), (1, 0)]).unwrap();
+        game.start();
+        game.open(0, 2);
+        assert_eq!(game.state, GameState::Running);
+        assert!(!game.board.cells[2].is_revealed);
+
+        let progress = game.solve_all();
+        assert_eq!(
+            progress,
+            SolveProgress {
+                flagged: 2,
+                revealed: 1
+            }
+        );
+        assert_eq!(game.state, GameState::Win);
+        assert!(game.solve_step().is_stuck());
+    }
+
+    #[test]
+    fn test_solve_all_ambiguous() {
+        let mut game = SweeperGame::new(2, 3, 0);
+
+        // Layout
+        // x .
+        // 1 1
+        // . .
+        // Both top cells are equally likely to be the bomb
+        game.place_bombs(&[(0, 0)]).unwrap();
+        game.start();
+        game.open(0, 2);
+        assert_eq!(game.num_revealed, 4);
+
+        assert!(game.solve_all().is_stuck());
+        assert_eq!(game.state, GameState::Running);
+        assert_eq!(game.num_revealed, 4);
+        assert_eq!(game.num_flags, 0);
+        assert!(!game.board.cells[0].is_revealed && !game.board.cells[1].is_revealed);
+    }
+
+    #[test]
+    fn test_undecidable_count() {
+        let mut game = SweeperGame::new(2, 3, 0);
+
+        // Layout
+        // x .
+        // 1 1
+        // . .
+        game.place_bombs(&[(0, 0)]).unwrap();
+        game.start();
+        assert_eq!(game.undecidable_count(), 0);
+        game.open(0, 2);
+        assert_eq!(game.undecidable_count(), 2);
+
+        // A flag settles the 50/50
+        game.flag(0, 0);
+        assert_eq!(game.undecidable_count(), 0);
+
+        let mut game = SweeperGame::new(3, 3, 0);
+
+        // Layout
+        // . 1 x
+        // . 2 .
+        // . 1 x
+        // The top and middle numbers put the bottom right bomb beyond single-point deductions
+        game.place_bombs(&[(2, 0), (2, 2)]).unwrap();
+        game.start();
+        game.open(0, 0);
+        assert!(game.solve_step().is_stuck());
+        assert_eq!(game.undecidable_count(), 0);
+    }
+
+    #[test]
+    fn test_is_cursor_provably_safe() {
+        let safe_cells = |game: &SweeperGame| {
+            let mut safe = Vec::new();
+            for y in 0..game.get_height() as isize {
+                for x in 0..game.get_width() as isize {
+                    if game.is_cursor_provably_safe(x, y) {
+                        safe.push((x, y));
+                    }
+                }
+            }
+            safe
+        };
+
+        let mut game = SweeperGame::new(3, 3, 0);
+
+        // Layout
+        // x x .
+        // 2 2 1
+        // . . .
+        game.place_bombs(&[(0, 0), (1, 0)]).unwrap();
+        game.start();
+        assert!(safe_cells(&game).is_empty());
+        game.open(0, 2);
+        assert_eq!(safe_cells(&game), vec![(2, 0)]);
+        assert!(!game.is_cursor_provably_safe(3, 0));
+
+        // Same once the bombs are flagged
+        game.flag(0, 0);
+        game.flag(1, 0);
+        assert_eq!(safe_cells(&game), vec![(2, 0)]);
+
+        let mut game = SweeperGame::new(3, 3, 0);
+
+        // Layout
+        // . 1 x
+        // . 2 .
+        // . 1 x
+        game.place_bombs(&[(2, 0), (2, 2)]).unwrap();
+        game.start();
+        game.open(0, 0);
+        assert!(safe_cells(&game).is_empty());
+    }
+}